@@ -31,6 +31,23 @@ pub struct OsError {
   error: platform_impl::OsError,
 }
 
+/// The error type for when the event loop can't be created.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum EventLoopError {
+  /// No display server could be connected to, e.g. `DISPLAY` and `WAYLAND_DISPLAY` are unset.
+  NoDisplay,
+  /// The display server was connected to through a backend tao doesn't support, or an unknown
+  /// backend was requested. Contains the name of that backend.
+  UnsupportedBackend(String),
+  /// The explicitly requested display backend isn't available. Contains the name of that backend.
+  BackendUnavailable(String),
+  /// The event loop was being created outside of the main thread.
+  NotMainThread,
+  /// The OS cannot create the event loop.
+  Os(OsError),
+}
+
 impl NotSupportedError {
   #[inline]
   #[allow(dead_code)]
@@ -71,6 +88,22 @@ impl fmt::Display for ExternalError {
   }
 }
 
+impl fmt::Display for EventLoopError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+    match self {
+      EventLoopError::NoDisplay => f.pad("failed to connect to a display server"),
+      EventLoopError::UnsupportedBackend(backend) => {
+        f.pad(&format!("the display backend `{backend}` is not supported"))
+      }
+      EventLoopError::BackendUnavailable(backend) => {
+        f.pad(&format!("the display backend `{backend}` is not available"))
+      }
+      EventLoopError::NotMainThread => f.pad("the event loop must be created on the main thread"),
+      EventLoopError::Os(e) => e.fmt(f),
+    }
+  }
+}
+
 impl fmt::Debug for NotSupportedError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
     f.debug_struct("NotSupportedError").finish()
@@ -86,3 +119,4 @@ impl fmt::Display for NotSupportedError {
 impl error::Error for OsError {}
 impl error::Error for ExternalError {}
 impl error::Error for NotSupportedError {}
+impl error::Error for EventLoopError {}
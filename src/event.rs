@@ -42,8 +42,9 @@ use std::{path::PathBuf, time::Instant};
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
   keyboard::{self, ModifiersState},
+  monitor::MonitorHandle,
   platform_impl,
-  window::{Theme, WindowId},
+  window::{Theme, WindowId, WindowState},
 };
 
 /// Describes a generic event.
@@ -115,6 +116,9 @@ pub enum Event<'a, T: 'static> {
   ///   the window is requested to redraw. But widgets it contains are usually not tied to its signal.
   ///   So if you really want to draw each component, please consider using `connect_draw` method
   ///   from [`WidgetExt`] directly.**
+  /// - **Linux:** Redraws requested with [`Window::request_redraw`](crate::window::Window::request_redraw)
+  ///   are delivered on the next frame of the window's GDK frame clock, so continuously redrawing
+  ///   is paced to the display's refresh rate.
   ///
   /// [`WidgetExt`]: https://gtk-rs.org/gtk3-rs/stable/latest/docs/gtk/prelude/trait.WidgetExt.html
   RedrawRequested(WindowId),
@@ -142,6 +146,60 @@ pub enum Event<'a, T: 'static> {
   /// - **Other**: Unsupported.
   #[non_exhaustive]
   Reopen { has_visible_windows: bool },
+
+  /// Emitted when a file descriptor watched with `EventLoopWindowTargetExtUnix::watch_fd` is ready.
+  ///
+  /// The watch is level-triggered: once the event is handled, it is emitted again as long as the
+  /// file descriptor is ready, so read or write it when handling the event. At most one event per
+  /// watch is pending at a time.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: Hang-ups and errors are reported once with the readiness the watch is interested
+  ///   in, after which the watch stops reporting.
+  /// - **Other**: Unsupported.
+  #[non_exhaustive]
+  FdReady {
+    watch_id: FdWatchId,
+    readiness: Interest,
+  },
+
+  /// Emitted when a timer added with `EventLoopWindowTargetExtUnix::add_timer` expires.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: Timers have millisecond precision.
+  /// - **Other**: Unsupported.
+  #[non_exhaustive]
+  Timer { timer_id: TimerId },
+
+  /// Emitted when the process receives a Unix signal registered with
+  /// `EventLoopBuilderExtUnix::with_signals`, such as `SIGTERM`.
+  ///
+  /// The default action of the signal, usually terminating the process, is replaced by this event.
+  /// Set `ControlFlow::Exit` to shut down gracefully.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Other**: Unsupported.
+  #[non_exhaustive]
+  Signal { signal: i32 },
+
+  /// Emitted when monitors are connected or disconnected, or when their geometry, work area, scale
+  /// factor or ICC profile changes.
+  ///
+  /// Changes that happen together are reported in a single event. The handles in `removed` still
+  /// report the last known properties of the monitors.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Other**: Unsupported.
+  #[non_exhaustive]
+  MonitorsChanged {
+    added: Vec<MonitorHandle>,
+    removed: Vec<MonitorHandle>,
+    changed: Vec<MonitorHandle>,
+  },
 }
 
 impl<T: Clone> Clone for Event<'static, T> {
@@ -170,11 +228,32 @@ impl<T: Clone> Clone for Event<'static, T> {
       } => Reopen {
         has_visible_windows: *has_visible_windows,
       },
+      FdReady {
+        watch_id,
+        readiness,
+      } => FdReady {
+        watch_id: *watch_id,
+        readiness: *readiness,
+      },
+      Timer { timer_id } => Timer {
+        timer_id: *timer_id,
+      },
+      Signal { signal } => Signal { signal: *signal },
+      MonitorsChanged {
+        added,
+        removed,
+        changed,
+      } => MonitorsChanged {
+        added: added.clone(),
+        removed: removed.clone(),
+        changed: changed.clone(),
+      },
     }
   }
 }
 
 impl<'a, T> Event<'a, T> {
+  #[allow(clippy::result_large_err)]
   pub fn map_nonuser_event<U>(self) -> Result<Event<'a, U>, Event<'a, T>> {
     use self::Event::*;
     match self {
@@ -194,6 +273,24 @@ impl<'a, T> Event<'a, T> {
       } => Ok(Reopen {
         has_visible_windows,
       }),
+      FdReady {
+        watch_id,
+        readiness,
+      } => Ok(FdReady {
+        watch_id,
+        readiness,
+      }),
+      Timer { timer_id } => Ok(Timer { timer_id }),
+      Signal { signal } => Ok(Signal { signal }),
+      MonitorsChanged {
+        added,
+        removed,
+        changed,
+      } => Ok(MonitorsChanged {
+        added,
+        removed,
+        changed,
+      }),
     }
   }
 
@@ -220,6 +317,24 @@ impl<'a, T> Event<'a, T> {
       } => Some(Reopen {
         has_visible_windows,
       }),
+      FdReady {
+        watch_id,
+        readiness,
+      } => Some(FdReady {
+        watch_id,
+        readiness,
+      }),
+      Timer { timer_id } => Some(Timer { timer_id }),
+      Signal { signal } => Some(Signal { signal }),
+      MonitorsChanged {
+        added,
+        removed,
+        changed,
+      } => Some(MonitorsChanged {
+        added,
+        removed,
+        changed,
+      }),
     }
   }
 }
@@ -254,6 +369,17 @@ pub enum StartCause {
 }
 
 /// Describes an event from a `Window`.
+///
+/// ## Timestamps
+///
+/// Input events carry a `timestamp` of when the input happened, to measure input latency or
+/// compute gesture velocities. Timestamps are monotonic and comparable with `Instant::now()`.
+///
+/// - **Linux:** Derived from the GDK event time, which has millisecond precision.
+/// - **Windows:** Derived from the message time, which has millisecond precision. Messages sent
+///   with `SendMessage` have no time of their own and use the time tao received them.
+/// - **macOS:** Derived from the `NSEvent` timestamp.
+/// - **iOS / Android:** The time tao received the event from the system.
 #[non_exhaustive]
 #[derive(Debug, PartialEq)]
 pub enum WindowEvent<'a> {
@@ -325,6 +451,9 @@ pub enum WindowEvent<'a> {
     ///
     /// Otherwise, this value is always `false`.
     is_synthetic: bool,
+
+    /// When the key was pressed or released, see [Timestamps](WindowEvent#timestamps).
+    timestamp: Instant,
   },
 
   /// The keyboard modifiers have changed.
@@ -340,6 +469,8 @@ pub enum WindowEvent<'a> {
     position: PhysicalPosition<f64>,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
+    /// When the cursor moved, see [Timestamps](WindowEvent#timestamps).
+    timestamp: Instant,
   },
 
   /// The cursor has entered the window.
@@ -355,6 +486,8 @@ pub enum WindowEvent<'a> {
     phase: TouchPhase,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
+    /// When the wheel moved, see [Timestamps](WindowEvent#timestamps).
+    timestamp: Instant,
   },
 
   /// An mouse button press has been received.
@@ -364,6 +497,12 @@ pub enum WindowEvent<'a> {
     button: MouseButton,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
+    /// How many times the button was pressed in a row, within the system's double-click time and
+    /// distance: `1` for a single click, `2` for a double click and so on. A release carries the
+    /// count of the press it ends.
+    click_count: u32,
+    /// When the button was pressed or released, see [Timestamps](WindowEvent#timestamps).
+    timestamp: Instant,
   },
 
   /// Touchpad pressure event.
@@ -377,6 +516,42 @@ pub enum WindowEvent<'a> {
     stage: i64,
   },
 
+  /// Two or more fingers are pinching or rotating on a touchpad.
+  ///
+  /// Both deltas are relative to the previous event of the gesture and `0.0` on `Started`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Other**: Unsupported.
+  TouchpadPinch {
+    device_id: DeviceId,
+    phase: TouchPhase,
+    /// The change in zoom, where a positive value means the fingers are moving apart. Adding up
+    /// the deltas of a gesture gives its total scale minus `1.0`.
+    scale_delta: f64,
+    /// The change in angle, in radians, clockwise being positive.
+    rotation_delta: f64,
+    fingers: u32,
+    /// When the gesture event happened, see [Timestamps](WindowEvent#timestamps).
+    timestamp: Instant,
+  },
+
+  /// Three or more fingers are swiping on a touchpad.
+  ///
+  /// `delta` is how far the center of the fingers moved since the previous event of the gesture.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Other**: Unsupported.
+  TouchpadSwipe {
+    device_id: DeviceId,
+    phase: TouchPhase,
+    delta: PhysicalPosition<f64>,
+    fingers: u32,
+    /// When the gesture event happened, see [Timestamps](WindowEvent#timestamps).
+    timestamp: Instant,
+  },
+
   /// Motion on some analog axis. May report data redundant to other, more specific events.
   AxisMotion {
     device_id: DeviceId,
@@ -387,6 +562,15 @@ pub enum WindowEvent<'a> {
   /// Touch event has been received
   Touch(Touch),
 
+  /// A stylus moved, touched or left the surface of a tablet, or one of its buttons changed.
+  ///
+  /// The regular cursor and mouse events are still emitted for the stylus.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Other**: Unsupported.
+  Pen(Pen),
+
   /// The window's scale factor has changed.
   ///
   /// The following user actions can cause DPI changes:
@@ -415,6 +599,30 @@ pub enum WindowEvent<'a> {
   /// - **Linux / Android / iOS:** Unsupported
   ThemeChanged(Theme),
 
+  /// The window was maximized, minimized, made fullscreen, focused, tiled, made sticky or kept
+  /// above or below other windows, or left one of these states. Contains the new state.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Windows / Android / iOS:** Unsupported
+  StateChanged(WindowState),
+
+  /// The window became fully hidden from the user, or visible again.
+  ///
+  /// A window is occluded while it is minimized, on another workspace or fully covered by other
+  /// windows. Occluded windows only receive [`Event::RedrawRequested`] when it is requested with
+  /// [`Window::request_redraw`](crate::window::Window::request_redraw), so applications can stop
+  /// rendering until they are visible again. Hiding a window with
+  /// [`Window::set_visible`](crate::window::Window::set_visible) doesn't make it occluded.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Being covered by other windows is only detected on X11 without a compositor. On
+  ///   Wayland, a window is also considered occluded when the compositor stops sending frame
+  ///   callbacks while a redraw is pending.
+  /// - **macOS / Windows / Android / iOS:** Unsupported
+  Occluded(bool),
+
   /// The window decorations has been clicked.
   ///
   /// ## Platform-specific
@@ -440,10 +648,12 @@ impl Clone for WindowEvent<'static> {
         device_id,
         event,
         is_synthetic,
+        timestamp,
       } => KeyboardInput {
         device_id: *device_id,
         event: event.clone(),
         is_synthetic: *is_synthetic,
+        timestamp: *timestamp,
       },
 
       ModifiersChanged(modifiers) => ModifiersChanged(*modifiers),
@@ -452,10 +662,12 @@ impl Clone for WindowEvent<'static> {
         device_id,
         position,
         modifiers,
+        timestamp,
       } => CursorMoved {
         device_id: *device_id,
         position: *position,
         modifiers: *modifiers,
+        timestamp: *timestamp,
       },
       CursorEntered { device_id } => CursorEntered {
         device_id: *device_id,
@@ -469,11 +681,13 @@ impl Clone for WindowEvent<'static> {
         delta,
         phase,
         modifiers,
+        timestamp,
       } => MouseWheel {
         device_id: *device_id,
         delta: *delta,
         phase: *phase,
         modifiers: *modifiers,
+        timestamp: *timestamp,
       },
       #[allow(deprecated)]
       MouseInput {
@@ -481,11 +695,15 @@ impl Clone for WindowEvent<'static> {
         state,
         button,
         modifiers,
+        click_count,
+        timestamp,
       } => MouseInput {
         device_id: *device_id,
         state: *state,
         button: *button,
         modifiers: *modifiers,
+        click_count: *click_count,
+        timestamp: *timestamp,
       },
       TouchpadPressure {
         device_id,
@@ -496,6 +714,34 @@ impl Clone for WindowEvent<'static> {
         pressure: *pressure,
         stage: *stage,
       },
+      TouchpadPinch {
+        device_id,
+        phase,
+        scale_delta,
+        rotation_delta,
+        fingers,
+        timestamp,
+      } => TouchpadPinch {
+        device_id: *device_id,
+        phase: *phase,
+        scale_delta: *scale_delta,
+        rotation_delta: *rotation_delta,
+        fingers: *fingers,
+        timestamp: *timestamp,
+      },
+      TouchpadSwipe {
+        device_id,
+        phase,
+        delta,
+        fingers,
+        timestamp,
+      } => TouchpadSwipe {
+        device_id: *device_id,
+        phase: *phase,
+        delta: *delta,
+        fingers: *fingers,
+        timestamp: *timestamp,
+      },
       AxisMotion {
         device_id,
         axis,
@@ -506,7 +752,10 @@ impl Clone for WindowEvent<'static> {
         value: *value,
       },
       Touch(touch) => Touch(*touch),
+      Pen(pen) => Pen(*pen),
       ThemeChanged(theme) => ThemeChanged(*theme),
+      StateChanged(state) => StateChanged(*state),
+      Occluded(occluded) => Occluded(*occluded),
       ScaleFactorChanged { .. } => {
         unreachable!("Static event can't be about scale factor changing")
       }
@@ -532,10 +781,12 @@ impl<'a> WindowEvent<'a> {
         device_id,
         event,
         is_synthetic,
+        timestamp,
       } => Some(KeyboardInput {
         device_id,
         event,
         is_synthetic,
+        timestamp,
       }),
       ModifiersChanged(modifiers) => Some(ModifiersChanged(modifiers)),
       #[allow(deprecated)]
@@ -543,10 +794,12 @@ impl<'a> WindowEvent<'a> {
         device_id,
         position,
         modifiers,
+        timestamp,
       } => Some(CursorMoved {
         device_id,
         position,
         modifiers,
+        timestamp,
       }),
       CursorEntered { device_id } => Some(CursorEntered { device_id }),
       CursorLeft { device_id } => Some(CursorLeft { device_id }),
@@ -556,11 +809,13 @@ impl<'a> WindowEvent<'a> {
         delta,
         phase,
         modifiers,
+        timestamp,
       } => Some(MouseWheel {
         device_id,
         delta,
         phase,
         modifiers,
+        timestamp,
       }),
       #[allow(deprecated)]
       MouseInput {
@@ -568,11 +823,15 @@ impl<'a> WindowEvent<'a> {
         state,
         button,
         modifiers,
+        click_count,
+        timestamp,
       } => Some(MouseInput {
         device_id,
         state,
         button,
         modifiers,
+        click_count,
+        timestamp,
       }),
       TouchpadPressure {
         device_id,
@@ -583,6 +842,34 @@ impl<'a> WindowEvent<'a> {
         pressure,
         stage,
       }),
+      TouchpadPinch {
+        device_id,
+        phase,
+        scale_delta,
+        rotation_delta,
+        fingers,
+        timestamp,
+      } => Some(TouchpadPinch {
+        device_id,
+        phase,
+        scale_delta,
+        rotation_delta,
+        fingers,
+        timestamp,
+      }),
+      TouchpadSwipe {
+        device_id,
+        phase,
+        delta,
+        fingers,
+        timestamp,
+      } => Some(TouchpadSwipe {
+        device_id,
+        phase,
+        delta,
+        fingers,
+        timestamp,
+      }),
       AxisMotion {
         device_id,
         axis,
@@ -593,7 +880,10 @@ impl<'a> WindowEvent<'a> {
         value,
       }),
       Touch(touch) => Some(Touch(touch)),
+      Pen(pen) => Some(Pen(pen)),
       ThemeChanged(theme) => Some(ThemeChanged(theme)),
+      StateChanged(state) => Some(StateChanged(state)),
+      Occluded(occluded) => Some(Occluded(occluded)),
       ScaleFactorChanged { .. } => None,
       DecorationsClick => Some(DecorationsClick),
     }
@@ -620,6 +910,27 @@ impl DeviceId {
   }
 }
 
+/// Identifier of a file descriptor watch.
+///
+/// Returned by `FdWatch::id` and carried by [`Event::FdReady`] to tell watches apart.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FdWatchId(pub(crate) u64);
+
+/// Identifier of a timer, carried by [`Event::Timer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(pub(crate) u64);
+
+bitflags! {
+  /// The kinds of readiness of a watched file descriptor.
+  #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+  pub struct Interest: u8 {
+    /// The file descriptor can be read from.
+    const READ = 1 << 0;
+    /// The file descriptor can be written to.
+    const WRITE = 1 << 1;
+  }
+}
+
 /// Represents raw hardware events that are not associated with any particular window.
 ///
 /// Useful for interactions that diverge significantly from a conventional 2D GUI, such as 3D camera or first-person
@@ -821,6 +1132,52 @@ pub struct Touch {
   pub force: Option<Force>,
   /// Unique identifier of a finger.
   pub id: u64,
+  /// When the touch happened, see [Timestamps](WindowEvent#timestamps).
+  pub timestamp: Instant,
+}
+
+/// Represents a stylus event, see [`WindowEvent::Pen`].
+///
+/// The axes that the tablet doesn't report are `None`.
+///
+/// ## Platform-specific
+///
+/// - **Linux:** Read from the axes of GDK pen and eraser devices, which GTK gets through
+///   XInput 2 on X11 and the tablet protocol on Wayland.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pen {
+  pub device_id: DeviceId,
+  /// `Started` when the tip touches the surface, `Ended` when it is lifted, and `Moved` when the
+  /// stylus moves or one of its buttons changes, whether it is touching the surface or hovering.
+  pub phase: TouchPhase,
+  pub location: PhysicalPosition<f64>,
+  /// Whether the tip is touching the surface.
+  pub contact: bool,
+  /// How hard the tip is pressed, between 0 and 1.
+  pub pressure: Option<f64>,
+  /// The tilt of the stylus along the X and Y axes, each between -1 and 1.
+  pub tilt: Option<(f64, f64)>,
+  /// The rotation of the stylus around its own axis.
+  pub rotation: Option<f64>,
+  /// The distance of the tip from the surface, between 0 and 1.
+  pub distance: Option<f64>,
+  /// Whether the eraser end of the stylus is being used.
+  pub eraser: bool,
+  /// The barrel buttons that are pressed.
+  pub buttons: PenButtons,
+  /// When the stylus event happened, see [Timestamps](WindowEvent#timestamps).
+  pub timestamp: Instant,
+}
+
+bitflags! {
+  /// The barrel buttons of a stylus.
+  #[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+  pub struct PenButtons: u8 {
+    /// The button closest to the tip.
+    const BARREL = 1 << 0;
+    /// The second button, on styluses that have one.
+    const SECONDARY_BARREL = 1 << 1;
+  }
 }
 
 /// Describes the force of a touch event
@@ -904,6 +1261,10 @@ pub enum MouseButton {
   Left,
   Right,
   Middle,
+  /// The button usually bound to "go back", often the fourth button on a mouse.
+  Back,
+  /// The button usually bound to "go forward", often the fifth button on a mouse.
+  Forward,
   Other(u16),
 }
 
@@ -18,9 +18,9 @@ use std::{error, fmt, marker::PhantomData, ops::Deref};
 
 use crate::{
   dpi::PhysicalPosition,
-  error::ExternalError,
+  error::{EventLoopError, ExternalError},
   event::Event,
-  monitor::MonitorHandle,
+  monitor::{MonitorHandle, MonitorId},
   platform_impl,
   window::{ProgressBarState, Theme},
 };
@@ -103,13 +103,17 @@ impl<T> EventLoopBuilder<T> {
   /// any thread.
   ///
   /// Usage will result in display backend initialisation, this can be controlled on linux
-  /// using an environment variable `WINIT_UNIX_BACKEND`. Legal values are `x11` and `wayland`.
-  /// If it is not set, winit will try to connect to a wayland connection, and if it fails will
-  /// fallback on x11. If this variable is set with any other value, winit will panic.
+  /// using an environment variable `TAO_UNIX_BACKEND` or `EventLoopBuilderExtUnix::with_backend`.
+  /// Legal values are `auto`, `x11` and `wayland`. If it is not set, tao will try to connect to a
+  /// wayland connection, and if it fails will fallback on x11. If this variable is set with any
+  /// other value, this panics, while [`EventLoopBuilder::try_build`] returns
+  /// [`EventLoopError::UnsupportedBackend`].
   ///
   /// ## Platform-specific
   ///
   /// - **iOS:** Can only be called on the main thread.
+  ///
+  /// See [`EventLoopBuilder::try_build`] for a version that doesn't panic.
   #[inline]
   pub fn build(&mut self) -> EventLoop<T> {
     EventLoop {
@@ -117,6 +121,29 @@ impl<T> EventLoopBuilder<T> {
       _marker: PhantomData,
     }
   }
+
+  /// Attempts to build a new event loop.
+  ///
+  /// Unlike [`EventLoopBuilder::build`], this returns an [`EventLoopError`] instead of panicking
+  /// when the event loop can't be created, for example when there is no display server to connect
+  /// to. This allows applications to fall back to a headless mode.
+  ///
+  /// On Linux, an invalid `TAO_UNIX_BACKEND` value returns [`EventLoopError::UnsupportedBackend`],
+  /// see [`EventLoopBuilder::build`] for the legal values.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Always returns `Ok`, failures still panic like in
+  ///   [`EventLoopBuilder::build`].
+  #[inline]
+  // Windows takes the attributes mutably.
+  #[allow(clippy::unnecessary_mut_passed)]
+  pub fn try_build(&mut self) -> Result<EventLoop<T>, EventLoopError> {
+    Ok(EventLoop {
+      event_loop: platform_impl::EventLoop::try_new(&mut self.platform_specific)?,
+      _marker: PhantomData,
+    })
+  }
 }
 
 /// Set by the user callback given to the `EventLoop::run` method.
@@ -262,6 +289,14 @@ impl<T> EventLoopWindowTarget<T> {
       .map(|inner| MonitorHandle { inner })
   }
 
+  /// Returns the monitor with the given identifier, if it is connected.
+  #[inline]
+  pub fn monitor_from_id(&self, id: &MonitorId) -> Option<MonitorHandle> {
+    self
+      .available_monitors()
+      .find(|monitor| monitor.id() == *id)
+  }
+
   /// Change [`DeviceEvent`] filter mode.
   ///
   /// Since the [`DeviceEvent`] capture can lead to high CPU usage for unfocused windows, tao
@@ -353,7 +388,7 @@ impl<T> rwh_06::HasDisplayHandle for EventLoopWindowTarget<T> {
 
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
-  event_loop_proxy: platform_impl::EventLoopProxy<T>,
+  pub(crate) event_loop_proxy: platform_impl::EventLoopProxy<T>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -370,6 +405,12 @@ impl<T: 'static> EventLoopProxy<T> {
   /// function.
   ///
   /// Returns an `Err` if the associated `EventLoop` no longer exists.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Blocks while the queue is full if a capacity was set with
+  ///   `EventLoopBuilderExtUnix::with_user_event_capacity`. Don't call it from the event loop
+  ///   thread in that case, as it would never be drained.
   pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
     self.event_loop_proxy.send_event(event)
   }
@@ -394,6 +435,36 @@ impl<T> fmt::Display for EventLoopClosed<T> {
 
 impl<T: fmt::Debug> error::Error for EventLoopClosed<T> {}
 
+/// The error that is returned when an `EventLoopProxy` can't queue an event without blocking.
+/// Contains the original event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TrySendError<T> {
+  /// The user event queue is at its capacity.
+  Full(T),
+  /// The `EventLoop` no longer exists.
+  Closed(T),
+}
+
+impl<T> TrySendError<T> {
+  /// Returns the event that couldn't be sent.
+  pub fn into_inner(self) -> T {
+    match self {
+      TrySendError::Full(event) | TrySendError::Closed(event) => event,
+    }
+  }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TrySendError::Full(_) => f.write_str("The `EventLoop` user event queue is full"),
+      TrySendError::Closed(_) => f.write_str("Tried to wake up a closed `EventLoop`"),
+    }
+  }
+}
+
+impl<T: fmt::Debug> error::Error for TrySendError<T> {}
+
 /// Fiter controlling the propagation of device events.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum DeviceEventFilter {
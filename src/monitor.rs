@@ -108,6 +108,45 @@ impl std::fmt::Display for VideoMode {
   }
 }
 
+/// An identifier of a monitor that stays the same across reconnections and restarts, see
+/// [`MonitorHandle::id`].
+///
+/// It can be stored, for example in the user settings, and looked up later with
+/// [`EventLoopWindowTarget::monitor_from_id`](crate::event_loop::EventLoopWindowTarget::monitor_from_id).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MonitorId(String);
+
+impl MonitorId {
+  /// Returns the identifier as a string, which can be turned back into a `MonitorId` with
+  /// `From<String>`.
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl From<String> for MonitorId {
+  fn from(id: String) -> Self {
+    MonitorId(id)
+  }
+}
+
+impl std::fmt::Display for MonitorId {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+/// A rectangle on the screen, in physical pixels.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+  /// The top-left corner, relative to the larger full screen area.
+  pub position: PhysicalPosition<i32>,
+  /// The width and height, extending right and down from `position`.
+  pub size: PhysicalSize<u32>,
+}
+
 /// Handle to a monitor.
 ///
 /// Allows you to retrieve information about a given monitor and can be used in [`Window`] creation.
@@ -127,6 +166,28 @@ impl MonitorHandle {
     self.inner.name()
   }
 
+  /// Returns an identifier of the monitor that stays the same when it is reconnected, the
+  /// monitors are rearranged or the system restarts.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** On X11, built from the manufacturer, product code and serial number in the EDID
+  ///   of the monitor. Identical monitors without a serial number are told apart by their
+  ///   connector, and monitors without an EDID use the connector alone. On Wayland, the
+  ///   manufacturer and model, followed by the order of the monitor from left to right, then top
+  ///   to bottom among identical ones. As GDK doesn't expose the connector there, identical
+  ///   monitors swap identifiers when they are rearranged, and monitors without a manufacturer or
+  ///   model are all treated as identical.
+  /// - **Windows:** The device interface path of the monitor, which names its model and the port
+  ///   it is connected to. Falls back to the device name, such as `\\.\DISPLAY1`, which is not
+  ///   stable across reconnections.
+  /// - **macOS:** The UUID of the display.
+  /// - **iOS / Android:** The name of the monitor.
+  #[inline]
+  pub fn id(&self) -> MonitorId {
+    MonitorId(self.inner.id())
+  }
+
   /// Returns the monitor's resolution.
   #[inline]
   pub fn size(&self) -> PhysicalSize<u32> {
@@ -140,18 +201,68 @@ impl MonitorHandle {
     self.inner.position()
   }
 
+  /// Returns the part of the monitor that windows can use, which excludes panels, docks and
+  /// taskbars.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** On X11, this needs the window manager to set `_NET_WORKAREA`. On Wayland, it is
+  ///   the full monitor.
+  /// - **iOS / Android:** The full monitor.
+  #[inline]
+  pub fn work_area(&self) -> Rect {
+    self.inner.work_area()
+  }
+
   /// Returns the scale factor that can be used to map logical pixels to physical pixels, and vice versa.
   ///
   /// See the [`dpi`](crate::dpi) module for more information.
   ///
   /// ## Platform-specific
   ///
+  /// - **Linux:** On X11, derived from `Xft.dpi` or the monitor's physical size and can be
+  ///   fractional. Can be overridden with `EventLoopBuilderExtUnix::with_scale_factor_override`.
   /// - **Android:** Always returns 1.0.
   #[inline]
   pub fn scale_factor(&self) -> f64 {
     self.inner.scale_factor()
   }
 
+  /// Returns the current refresh rate of the monitor in millihertz, if it is known.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported, returns `None`.
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    self.inner.refresh_rate_millihertz()
+  }
+
+  /// Returns the width and height of the monitor in millimetres, if it is known.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / iOS / Android:** Unsupported, returns `None`.
+  #[inline]
+  pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+    self.inner.physical_size_mm()
+  }
+
+  /// Returns the ICC color profile of the monitor, if one is set.
+  ///
+  /// [`Event::MonitorsChanged`](crate::event::Event::MonitorsChanged) reports the monitor as
+  /// changed when its profile changes.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Only available on X11, where color managers store the profiles in the
+  ///   `_ICC_PROFILE` and `_ICC_PROFILE_n` properties of the root window.
+  /// - **Windows / macOS / iOS / Android:** Unsupported, returns `None`.
+  #[inline]
+  pub fn icc_profile(&self) -> Option<Vec<u8>> {
+    self.inner.icc_profile()
+  }
+
   /// Returns all fullscreen video modes supported by this monitor.
   ///
   /// ## Platform-specific
@@ -13,9 +13,10 @@
 //!  - `linux`
 //!  - `windows`
 //!
-//! And the following platform-specific module:
+//! And the following platform-specific modules:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `pump_events` (available on `unix`)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
@@ -23,6 +24,7 @@ pub mod android;
 pub mod ios;
 pub mod linux;
 pub mod macos;
+pub mod pump_events;
 pub mod run_return;
 pub mod unix;
 pub mod windows;
@@ -0,0 +1,63 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+
+use std::time::Duration;
+
+use crate::{
+  event::Event,
+  event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+};
+
+/// The status returned by [`EventLoopExtPumpEvents::pump_events`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PumpStatus {
+  /// The event loop is still running, keep calling `pump_events`.
+  Continue,
+  /// The event loop exited with the given exit code.
+  Exit(i32),
+}
+
+/// Additional methods on `EventLoop` to drive it from an external loop.
+pub trait EventLoopExtPumpEvents {
+  /// A type provided by the user that can be passed through `Event::UserEvent`.
+  type UserEvent;
+
+  /// Processes the pending events and returns control to the caller.
+  ///
+  /// Each call runs one iteration of the event loop, from `NewEvents` to `RedrawEventsCleared`,
+  /// and returns [`PumpStatus::Continue`]. The control flow is kept between calls, and
+  /// `StartCause::Init` is only sent on the first call. Once `control_flow` is set to
+  /// `ControlFlow::ExitWithCode`, `LoopDestroyed` is sent and [`PumpStatus::Exit`] is returned;
+  /// calling `pump_events` again after that starts the loop over.
+  ///
+  /// When the control flow is `Wait` or `WaitUntil` and nothing is pending, this blocks for at
+  /// most `timeout`, returning `PumpStatus::Continue` without running an iteration if it elapses.
+  /// Pass `Some(Duration::ZERO)` to never block, or `None` to wait as long as the control flow
+  /// asks.
+  ///
+  /// Don't mix this with [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return)
+  /// while the loop is running, as `run_return` always starts over.
+  fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow);
+}
+
+impl<T> EventLoopExtPumpEvents for EventLoop<T> {
+  type UserEvent = T;
+
+  fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow),
+  {
+    self.event_loop.pump_events(timeout, event_handler)
+  }
+}
@@ -10,16 +10,29 @@
   target_os = "openbsd"
 ))]
 
-use std::{os::raw::c_int, sync::Arc};
+use std::{
+  future::Future,
+  num::NonZeroUsize,
+  os::{raw::c_int, unix::io::RawFd},
+  sync::Arc,
+  time::Duration,
+};
 
 // XConnection utilities
 #[doc(hidden)]
 pub use crate::platform_impl::x11;
 
 pub use crate::platform_impl::EventLoop as UnixEventLoop;
+pub use crate::platform_impl::{
+  Backend, FdWatch, KeyRepeatSettings, MonitorRotation, RunOnMain, ScaleFactorOverride, SendEvent,
+  SubpixelLayout, UserEventPriority,
+};
 use crate::{
   error::{ExternalError, OsError},
-  event_loop::{EventLoopBuilder, EventLoopWindowTarget},
+  event::{Interest, TimerId},
+  event_loop::{
+    EventLoopBuilder, EventLoopClosed, EventLoopProxy, EventLoopWindowTarget, TrySendError,
+  },
   monitor::MonitorHandle,
   platform_impl::{x11::xdisplay::XError, Parent, Window as UnixWindow},
   window::{Window, WindowBuilder},
@@ -45,6 +58,92 @@ pub trait EventLoopBuilderExtUnix {
   ///
   /// If no application ID is given then some features (most notably application uniqueness) will be disabled.
   fn with_app_id<S: Into<String>>(&mut self, id: S) -> &mut Self;
+
+  /// Whether tao should generate key repeats itself instead of relying on the OS.
+  ///
+  /// When enabled, repeated key presses coming from the OS are dropped and tao emits
+  /// `KeyboardInput` events with `repeat` set to `true` on its own, using the timings reported by
+  /// [`EventLoopWindowTargetExtUnix::key_repeat_settings`]. This gives consistent repeat behavior
+  /// on Wayland, where compositors only advertise the repeat parameters to the client.
+  ///
+  /// Default is `false`.
+  fn with_synthetic_key_repeat(&mut self, synthetic: bool) -> &mut Self;
+
+  /// Set the display server backend to use.
+  ///
+  /// This restricts the backends GDK is allowed to use, for example [`Backend::X11`] makes tao
+  /// run through XWayland on Wayland sessions for features that are only available on X11, like
+  /// the global cursor position or moving windows.
+  ///
+  /// The `TAO_UNIX_BACKEND` environment variable takes precedence over this, its legal values
+  /// are `auto`, `x11` and `wayland`.
+  ///
+  /// If the requested backend isn't available, or GTK was already initialized with another
+  /// backend, [`EventLoopBuilder::try_build`] returns
+  /// [`EventLoopError::BackendUnavailable`](crate::error::EventLoopError::BackendUnavailable).
+  ///
+  /// Default is [`Backend::Auto`].
+  fn with_backend(&mut self, backend: Backend) -> &mut Self;
+
+  /// Deliver the given Unix signals, e.g. `libc::SIGTERM` or `libc::SIGINT`, as
+  /// [`Event::Signal`](crate::event::Event::Signal) instead of letting them run their default
+  /// action.
+  ///
+  /// This lets the application run its `LoopDestroyed` cleanup when it is asked to terminate.
+  /// Only `SIGHUP`, `SIGINT`, `SIGTERM`, `SIGUSR1`, `SIGUSR2` and `SIGWINCH` are supported, other
+  /// signals are ignored with a warning.
+  fn with_signals(&mut self, signals: &[c_int]) -> &mut Self;
+
+  /// Bound the number of user events that can be pending at once.
+  ///
+  /// Once the queue is full, [`EventLoopProxy::send_event`] blocks,
+  /// [`EventLoopProxyExtUnix::try_send_event`] fails with [`TrySendError::Full`] and
+  /// [`EventLoopProxyExtUnix::send_event_async`] waits, until the event loop takes events out of
+  /// it. This keeps fast producer threads from growing memory without bound.
+  ///
+  /// Default is unbounded.
+  fn with_user_event_capacity(&mut self, capacity: NonZeroUsize) -> &mut Self;
+
+  /// Set the priority of the user events sent without an explicit one.
+  ///
+  /// By default, user events are delivered in the order they were sent in, interleaved with the
+  /// window and device events. With [`UserEventPriority::High`], they are delivered before the
+  /// pending window and device events, with [`UserEventPriority::Low`] after them.
+  ///
+  /// Default is [`UserEventPriority::Normal`].
+  fn with_user_event_priority(&mut self, priority: UserEventPriority) -> &mut Self;
+
+  /// Deliver at most `budget` user events per event loop iteration.
+  ///
+  /// The remaining events are delivered in the following iterations, so a burst of user events
+  /// can't delay input processing and redraws indefinitely. Those iterations start right away, as
+  /// the events left over the budget are pending and cancel a
+  /// [`ControlFlow::Wait`](crate::event_loop::ControlFlow::Wait) like any other event.
+  ///
+  /// Default is unlimited.
+  fn with_user_event_budget(&mut self, budget: NonZeroUsize) -> &mut Self;
+
+  /// Move windows that end up outside of every monitor, because their monitor was disconnected or
+  /// the monitors were rearranged, to the center of the primary monitor.
+  ///
+  /// Windows are moved before [`Event::MonitorsChanged`](crate::event::Event::MonitorsChanged)
+  /// is emitted. This has no effect on Wayland, where the compositor places windows.
+  ///
+  /// Default is `false`.
+  fn with_relocate_orphaned_windows(&mut self, relocate: bool) -> &mut Self;
+
+  /// Force the scale factor reported by [`Window::scale_factor`] and
+  /// [`MonitorHandle::scale_factor`], and used to convert logical sizes and positions, instead of
+  /// the one derived from the system.
+  ///
+  /// Without an override, X11 uses the fractional scale of `Xft.dpi`, or of the monitor's physical
+  /// size if `Xft.dpi` isn't set, and Wayland uses GDK's integer scale.
+  ///
+  /// The `TAO_SCALE_FACTOR` environment variable takes precedence over this, it is either a
+  /// single scale factor like `1.5` or per monitor scale factors like `DP-1=2;HDMI-1=1.25`.
+  ///
+  /// Default is `None`.
+  fn with_scale_factor_override(&mut self, scale_factor: ScaleFactorOverride) -> &mut Self;
 }
 
 impl<T> EventLoopBuilderExtUnix for EventLoopBuilder<T> {
@@ -58,6 +157,54 @@ impl<T> EventLoopBuilderExtUnix for EventLoopBuilder<T> {
     self.platform_specific.app_id = Some(id.into());
     self
   }
+
+  #[inline]
+  fn with_synthetic_key_repeat(&mut self, synthetic: bool) -> &mut Self {
+    self.platform_specific.synthetic_key_repeat = synthetic;
+    self
+  }
+
+  #[inline]
+  fn with_backend(&mut self, backend: Backend) -> &mut Self {
+    self.platform_specific.backend = backend;
+    self
+  }
+
+  #[inline]
+  fn with_signals(&mut self, signals: &[c_int]) -> &mut Self {
+    self.platform_specific.signals = signals.to_vec();
+    self
+  }
+
+  #[inline]
+  fn with_user_event_capacity(&mut self, capacity: NonZeroUsize) -> &mut Self {
+    self.platform_specific.user_event_capacity = Some(capacity);
+    self
+  }
+
+  #[inline]
+  fn with_user_event_priority(&mut self, priority: UserEventPriority) -> &mut Self {
+    self.platform_specific.user_event_priority = priority;
+    self
+  }
+
+  #[inline]
+  fn with_user_event_budget(&mut self, budget: NonZeroUsize) -> &mut Self {
+    self.platform_specific.user_event_budget = Some(budget);
+    self
+  }
+
+  #[inline]
+  fn with_relocate_orphaned_windows(&mut self, relocate: bool) -> &mut Self {
+    self.platform_specific.relocate_orphaned_windows = relocate;
+    self
+  }
+
+  #[inline]
+  fn with_scale_factor_override(&mut self, scale_factor: ScaleFactorOverride) -> &mut Self {
+    self.platform_specific.scale_factor_override = Some(scale_factor);
+    self
+  }
 }
 
 /// Additional methods on `Window` that are specific to Unix.
@@ -150,6 +297,23 @@ pub trait WindowBuilderExtUnix {
   /// Whether to create a vertical `gtk::Box` and add it as the sole child of this window.
   /// Created by default.
   fn with_default_vbox(self, add: bool) -> WindowBuilder;
+
+  /// Whether GDK may merge consecutive pointer motions into a single `CursorMoved` event.
+  ///
+  /// Turning this off delivers every motion the system reports, which drawing applications need
+  /// to keep the detail of fast strokes.
+  ///
+  /// Default is `true`.
+  fn with_motion_compression(self, compression: bool) -> WindowBuilder;
+
+  /// Whether to recover the pointer motions that were merged by motion compression.
+  ///
+  /// When enabled, the samples GDK recorded between two motion events are delivered as
+  /// `CursorMoved` events with their own timestamp before the latest position. This depends on
+  /// the display server keeping a motion history, which is usually only the case on X11.
+  ///
+  /// Default is `false`.
+  fn with_motion_history(self, history: bool) -> WindowBuilder;
 }
 
 impl WindowBuilderExtUnix for WindowBuilder {
@@ -193,6 +357,16 @@ impl WindowBuilderExtUnix for WindowBuilder {
     self.platform_specific.default_vbox = add;
     self
   }
+
+  fn with_motion_compression(mut self, compression: bool) -> WindowBuilder {
+    self.platform_specific.motion_compression = compression;
+    self
+  }
+
+  fn with_motion_history(mut self, history: bool) -> WindowBuilder {
+    self.platform_specific.motion_history = history;
+    self
+  }
 }
 
 /// Additional methods on `EventLoopWindowTarget` that are specific to Unix.
@@ -218,6 +392,37 @@ pub trait EventLoopWindowTargetExtUnix {
 
   /// Sets the badge count on the taskbar
   fn set_badge_count(&self, count: Option<i64>, desktop_filename: Option<String>);
+
+  /// Returns the key repeat delay and interval configured on the system.
+  ///
+  /// On X11 these are the XKB auto-repeat controls. On Wayland they are read from the
+  /// `org.gnome.desktop.peripherals.keyboard` settings, falling back to their defaults if the
+  /// schema isn't installed.
+  fn key_repeat_settings(&self) -> KeyRepeatSettings;
+
+  /// Spawns a future on the event loop thread.
+  ///
+  /// The future doesn't need to be `Send`. It is polled by the GLib main context that drives the
+  /// event loop, so it only makes progress while the loop is running.
+  fn spawn_local<F: Future<Output = ()> + 'static>(&self, future: F);
+
+  /// Watches a file descriptor, such as a socket or a pipe, for readiness.
+  ///
+  /// The event loop wakes up and emits [`Event::FdReady`](crate::event::Event::FdReady) whenever
+  /// the file descriptor is ready for any of `interest`. The watch lasts until the returned
+  /// [`FdWatch`] or the event loop is dropped, and the file descriptor must stay open until then.
+  fn watch_fd(&self, fd: RawFd, interest: Interest) -> FdWatch;
+
+  /// Adds a timer that emits [`Event::Timer`](crate::event::Event::Timer) after `duration`, and
+  /// again every `duration` afterwards if `repeating` is true.
+  ///
+  /// Unlike `ControlFlow::WaitUntil`, any number of timers can be pending at once and they wake
+  /// the event loop regardless of the control flow.
+  fn add_timer(&self, duration: Duration, repeating: bool) -> TimerId;
+
+  /// Cancels a timer added with [`EventLoopWindowTargetExtUnix::add_timer`]. Cancelling a timer
+  /// that already expired does nothing.
+  fn cancel_timer(&self, id: TimerId);
 }
 
 impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
@@ -264,6 +469,112 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
   fn set_badge_count(&self, count: Option<i64>, desktop_filename: Option<String>) {
     self.p.set_badge_count(count, desktop_filename);
   }
+
+  #[inline]
+  fn key_repeat_settings(&self) -> KeyRepeatSettings {
+    self.p.key_repeat_settings()
+  }
+
+  #[inline]
+  fn spawn_local<F: Future<Output = ()> + 'static>(&self, future: F) {
+    self.p.spawn_local(future)
+  }
+
+  #[inline]
+  fn watch_fd(&self, fd: RawFd, interest: Interest) -> FdWatch {
+    self.p.watch_fd(fd, interest)
+  }
+
+  #[inline]
+  fn add_timer(&self, duration: Duration, repeating: bool) -> TimerId {
+    self.p.add_timer(duration, repeating)
+  }
+
+  #[inline]
+  fn cancel_timer(&self, id: TimerId) {
+    self.p.cancel_timer(id)
+  }
+}
+
+/// Additional methods on `EventLoopProxy` that are specific to Unix.
+pub trait EventLoopProxyExtUnix {
+  /// A type provided by the user that can be passed through `Event::UserEvent`.
+  type UserEvent: 'static;
+
+  /// Runs `f` on the event loop thread and returns a future resolving to its result.
+  ///
+  /// The closure runs between events during the next loop iteration. The future resolves to
+  /// `Err` if the event loop is dropped before that.
+  fn run_on_main<F, R>(&self, f: F) -> RunOnMain<R>
+  where
+    F: FnOnce(&EventLoopWindowTarget<Self::UserEvent>) -> R + Send + 'static,
+    R: Send + 'static;
+
+  /// Like [`EventLoopProxy::send_event`], but fails with [`TrySendError::Full`] instead of
+  /// blocking when the queue set with [`EventLoopBuilderExtUnix::with_user_event_capacity`] is
+  /// full.
+  fn try_send_event(&self, event: Self::UserEvent) -> Result<(), TrySendError<Self::UserEvent>>;
+
+  /// Like [`EventLoopProxy::send_event`], but returns a future that waits for space in the queue
+  /// instead of blocking the thread.
+  fn send_event_async(&self, event: Self::UserEvent) -> SendEvent<Self::UserEvent>;
+
+  /// Like [`EventLoopProxyExtUnix::try_send_event`], but if an event sent with the same `key` is
+  /// still pending, it is replaced by `event`, so only the latest one is delivered.
+  ///
+  /// Replacing a pending event always succeeds, even if the queue is full.
+  fn send_event_coalesced(
+    &self,
+    key: u64,
+    event: Self::UserEvent,
+  ) -> Result<(), TrySendError<Self::UserEvent>>;
+
+  /// Like [`EventLoopProxy::send_event`], but with the given priority instead of the one set with
+  /// [`EventLoopBuilderExtUnix::with_user_event_priority`].
+  fn send_event_with_priority(
+    &self,
+    event: Self::UserEvent,
+    priority: UserEventPriority,
+  ) -> Result<(), EventLoopClosed<Self::UserEvent>>;
+}
+
+impl<T: 'static> EventLoopProxyExtUnix for EventLoopProxy<T> {
+  type UserEvent = T;
+
+  #[inline]
+  fn run_on_main<F, R>(&self, f: F) -> RunOnMain<R>
+  where
+    F: FnOnce(&EventLoopWindowTarget<T>) -> R + Send + 'static,
+    R: Send + 'static,
+  {
+    self.event_loop_proxy.run_on_main(f)
+  }
+
+  #[inline]
+  fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+    self.event_loop_proxy.try_send_event(event)
+  }
+
+  #[inline]
+  fn send_event_async(&self, event: T) -> SendEvent<T> {
+    self.event_loop_proxy.send_event_async(event)
+  }
+
+  #[inline]
+  fn send_event_coalesced(&self, key: u64, event: T) -> Result<(), TrySendError<T>> {
+    self.event_loop_proxy.send_event_coalesced(key, event)
+  }
+
+  #[inline]
+  fn send_event_with_priority(
+    &self,
+    event: T,
+    priority: UserEventPriority,
+  ) -> Result<(), EventLoopClosed<T>> {
+    self
+      .event_loop_proxy
+      .send_event_with_priority(event, priority)
+  }
 }
 
 unsafe extern "C" fn x_error_callback(
@@ -288,6 +599,24 @@ unsafe extern "C" fn x_error_callback(
 pub trait MonitorHandleExtUnix {
   /// Returns the gdk handle of the monitor.
   fn gdk_monitor(&self) -> &gtk::gdk::Monitor;
+
+  /// Returns the manufacturer of the monitor.
+  ///
+  /// On X11 this is the three-letter PNP ID from the EDID of the monitor, such as `DEL` or `SAM`.
+  fn manufacturer(&self) -> Option<String>;
+
+  /// Returns the name of the connector the monitor is plugged into, such as `DP-1` or `HDMI-A-1`.
+  ///
+  /// Only available on X11, through XRandR.
+  fn connector(&self) -> Option<String>;
+
+  /// Returns the order of the color subpixels of the monitor.
+  fn subpixel_layout(&self) -> SubpixelLayout;
+
+  /// Returns how much the picture of the monitor is rotated.
+  ///
+  /// Only available on X11, through XRandR.
+  fn rotation(&self) -> Option<MonitorRotation>;
 }
 
 impl MonitorHandleExtUnix for MonitorHandle {
@@ -295,4 +624,24 @@ impl MonitorHandleExtUnix for MonitorHandle {
   fn gdk_monitor(&self) -> &gtk::gdk::Monitor {
     &self.inner.monitor
   }
+
+  #[inline]
+  fn manufacturer(&self) -> Option<String> {
+    self.inner.manufacturer()
+  }
+
+  #[inline]
+  fn connector(&self) -> Option<String> {
+    self.inner.connector()
+  }
+
+  #[inline]
+  fn subpixel_layout(&self) -> SubpixelLayout {
+    self.inner.subpixel_layout()
+  }
+
+  #[inline]
+  fn rotation(&self) -> Option<MonitorRotation> {
+    self.inner.rotation()
+  }
 }
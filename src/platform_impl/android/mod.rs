@@ -76,6 +76,12 @@ macro_rules! call_event_handler {
 }
 
 impl<T: 'static> EventLoop<T> {
+  pub(crate) fn try_new(
+    attributes: &PlatformSpecificEventLoopAttributes,
+  ) -> Result<Self, error::EventLoopError> {
+    Ok(Self::new(attributes))
+  }
+
   pub(crate) fn new(_: &PlatformSpecificEventLoopAttributes) -> Self {
     let (sender, receiver) = crossbeam_channel::unbounded();
 
@@ -233,6 +239,7 @@ impl<T: 'static> EventLoop<T> {
                             location,
                             id: pointer.pointer_id() as u64,
                             force: None,
+                            timestamp: Instant::now(),
                           }),
                         };
                         call_event_handler!(
@@ -271,6 +278,7 @@ impl<T: 'static> EventLoop<T> {
                           platform_specific: KeyEventExtra {},
                         },
                         is_synthetic: false,
+                        timestamp: Instant::now(),
                       },
                     };
                     call_event_handler!(event_handler, self.window_target(), control_flow, event);
@@ -602,6 +610,10 @@ impl Window {
     false
   }
 
+  pub fn tiled_edges(&self) -> window::TiledEdges {
+    window::TiledEdges::empty()
+  }
+
   pub fn is_visible(&self) -> bool {
     log::warn!("`Window::is_visible` is ignored on Android");
     false
@@ -778,6 +790,10 @@ impl MonitorHandle {
     Some("Android Device".to_owned())
   }
 
+  pub fn id(&self) -> String {
+    self.name().unwrap_or_default()
+  }
+
   pub fn size(&self) -> PhysicalSize<u32> {
     // TODO decide how to get JNIENV
     if let Some(w) = ndk_glue::window_manager().as_ref() {
@@ -820,6 +836,13 @@ impl MonitorHandle {
     (0, 0).into()
   }
 
+  pub fn work_area(&self) -> monitor::Rect {
+    monitor::Rect {
+      position: self.position(),
+      size: self.size(),
+    }
+  }
+
   pub fn scale_factor(&self) -> f64 {
     let config = CONFIG.read().unwrap();
     config
@@ -828,6 +851,18 @@ impl MonitorHandle {
       .unwrap_or(1.0)
   }
 
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    None
+  }
+
+  pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+    None
+  }
+
+  pub fn icc_profile(&self) -> Option<Vec<u8>> {
+    None
+  }
+
   pub fn video_modes(&self) -> impl Iterator<Item = monitor::VideoMode> {
     let size = self.size().into();
     let mut v = Vec::new();
@@ -14,7 +14,7 @@ use crossbeam_channel::{self as channel, Receiver, Sender};
 
 use crate::{
   dpi::{LogicalSize, PhysicalPosition},
-  error::ExternalError,
+  error::{EventLoopError, ExternalError},
   event::Event,
   event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget},
   monitor::MonitorHandle as RootMonitorHandle,
@@ -109,6 +109,12 @@ pub struct EventLoop<T: 'static> {
 }
 
 impl<T: 'static> EventLoop<T> {
+  pub(crate) fn try_new(
+    attributes: &PlatformSpecificEventLoopAttributes,
+  ) -> Result<EventLoop<T>, EventLoopError> {
+    Ok(Self::new(attributes))
+  }
+
   pub(crate) fn new(_: &PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
     static mut SINGLETON_INIT: bool = false;
     unsafe {
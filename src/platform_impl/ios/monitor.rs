@@ -10,7 +10,7 @@ use std::{
 
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  monitor::{MonitorHandle as RootMonitorHandle, Rect, VideoMode as RootVideoMode},
   platform_impl::platform::{
     app_state,
     ffi::{id, nil, CGFloat, CGRect, CGSize, NSInteger, NSUInteger},
@@ -217,6 +217,10 @@ impl Inner {
     }
   }
 
+  pub fn id(&self) -> String {
+    self.name().unwrap_or_default()
+  }
+
   pub fn size(&self) -> PhysicalSize<u32> {
     unsafe {
       let bounds: CGRect = msg_send![self.ui_screen(), nativeBounds];
@@ -231,6 +235,13 @@ impl Inner {
     }
   }
 
+  pub fn work_area(&self) -> Rect {
+    Rect {
+      position: self.position(),
+      size: self.size(),
+    }
+  }
+
   pub fn scale_factor(&self) -> f64 {
     unsafe {
       let scale: CGFloat = msg_send![self.ui_screen(), nativeScale];
@@ -238,6 +249,18 @@ impl Inner {
     }
   }
 
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    None
+  }
+
+  pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+    None
+  }
+
+  pub fn icc_profile(&self) -> Option<Vec<u8>> {
+    None
+  }
+
   pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
     let mut modes = BTreeSet::new();
     unsafe {
@@ -5,6 +5,7 @@
 use std::{
   collections::HashMap,
   ffi::{c_char, CStr, CString},
+  time::Instant,
 };
 
 use objc2::runtime::{AnyClass as Class, AnyObject as Object, ClassBuilder as ClassDecl, Sel};
@@ -276,6 +277,7 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
               location: physical_location,
               force,
               phase,
+              timestamp: Instant::now(),
             }),
           }));
         }
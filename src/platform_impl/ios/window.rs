@@ -26,8 +26,8 @@ use crate::{
     monitor, set_badge_count, view, EventLoopWindowTarget, MonitorHandle,
   },
   window::{
-    CursorIcon, Fullscreen, ResizeDirection, Theme, UserAttentionType, WindowAttributes,
-    WindowId as RootWindowId, WindowSizeConstraints,
+    CursorIcon, Fullscreen, ResizeDirection, Theme, TiledEdges, UserAttentionType,
+    WindowAttributes, WindowId as RootWindowId, WindowSizeConstraints,
   },
 };
 
@@ -253,6 +253,10 @@ impl Inner {
     false
   }
 
+  pub fn tiled_edges(&self) -> TiledEdges {
+    TiledEdges::empty()
+  }
+
   pub fn is_visible(&self) -> bool {
     log::warn!("`Window::is_visible` is ignored on iOS");
     false
@@ -3,17 +3,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-  cell::RefCell,
-  collections::{HashSet, VecDeque},
-  error::Error,
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet, VecDeque},
+  future::Future,
+  num::NonZeroUsize,
+  os::unix::io::RawFd,
+  pin::Pin,
   process,
   rc::Rc,
-  sync::atomic::{AtomicBool, Ordering},
-  time::Instant,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  task::{Context, Poll, Waker},
+  time::{Duration, Instant},
 };
 
 use cairo::{RectangleInt, Region};
-use crossbeam_channel::SendError;
 use gdk::{Cursor, CursorType, EventKey, EventMask, ScrollDirection, WindowEdge, WindowState};
 use gio::Cancellable;
 use glib::{source::Priority, MainContext};
@@ -26,14 +32,15 @@ use gtk::{
 
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
-  error::ExternalError,
+  error::{EventLoopError, ExternalError},
   event::{
-    ElementState, Event, MouseButton, MouseScrollDelta, StartCause, TouchPhase, WindowEvent,
+    ElementState, Event, Interest, MouseScrollDelta, StartCause, TimerId, TouchPhase, WindowEvent,
   },
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, TrySendError},
   keyboard::ModifiersState,
   monitor::MonitorHandle as RootMonitorHandle,
-  platform_impl::platform::{device, DEVICE_ID},
+  platform::pump_events::PumpStatus,
+  platform_impl::platform::{device, OsError, DEVICE_ID},
   window::{
     CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, WindowId as RootWindowId,
   },
@@ -42,14 +49,18 @@ use crate::{
 use super::{
   keyboard,
   monitor::{self, MonitorHandle},
-  taskbar, util,
+  scale::{self, ScaleFactorOverride},
+  sources::{self, FdWatch},
+  taskbar,
+  user_events::{self, EventReceiver, EventSender, SendEvent, UserEventPriority, UserEventQueue},
+  util::{self, Occlusion},
   window::{WindowId, WindowRequest},
 };
 
 use taskbar::TaskbarIndicator;
 
 #[derive(Clone)]
-pub struct EventLoopWindowTarget<T> {
+pub struct EventLoopWindowTarget<T: 'static> {
   /// Gdk display
   pub(crate) display: gdk::Display,
   /// Gtk application
@@ -58,8 +69,14 @@ pub struct EventLoopWindowTarget<T> {
   pub(crate) windows: Rc<RefCell<HashSet<WindowId>>>,
   /// Window requests sender
   pub(crate) window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
-  /// Draw event sender
-  pub(crate) draw_tx: crossbeam_channel::Sender<WindowId>,
+  /// Event sender for GLib sources created by the user
+  pub(crate) event_tx: EventSender<T>,
+  /// Pending timers
+  pub(crate) timers: sources::Timers,
+  /// File descriptor watches
+  pub(crate) fd_watches: sources::FdWatches,
+  /// Key repeat settings of the system
+  pub(crate) key_repeat_settings: keyboard::KeyRepeatSettingsCache,
   _marker: std::marker::PhantomData<T>,
 }
 
@@ -150,6 +167,31 @@ impl<T> EventLoopWindowTarget<T> {
     util::cursor_position(self.is_wayland())
   }
 
+  #[inline]
+  pub fn key_repeat_settings(&self) -> keyboard::KeyRepeatSettings {
+    self.key_repeat_settings.get()
+  }
+
+  #[inline]
+  pub fn watch_fd(&self, fd: RawFd, interest: Interest) -> FdWatch {
+    sources::watch_fd(&self.fd_watches, fd, interest, self.event_tx.clone())
+  }
+
+  #[inline]
+  pub fn add_timer(&self, duration: Duration, repeating: bool) -> TimerId {
+    sources::add_timer(&self.timers, duration, repeating, self.event_tx.clone())
+  }
+
+  #[inline]
+  pub fn cancel_timer(&self, id: TimerId) {
+    sources::cancel_timer(&self.timers, id)
+  }
+
+  #[inline]
+  pub fn spawn_local<F: Future<Output = ()> + 'static>(&self, future: F) {
+    MainContext::default().spawn_local(future);
+  }
+
   #[inline]
   pub fn set_progress_bar(&self, progress: ProgressBarState) {
     if let Err(e) = self
@@ -185,19 +227,295 @@ pub struct EventLoop<T: 'static> {
   /// Window target.
   window_target: RootELW<T>,
   /// User event sender for EventLoopProxy
-  pub(crate) user_event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+  user_events: Arc<UserEventQueue<T>>,
+  /// Maximum number of user events delivered per iteration
+  user_event_budget: Option<NonZeroUsize>,
+  /// Number of user events delivered in the current iteration
+  delivered_user_events: usize,
   /// Event queue of EventLoop
-  events: crossbeam_channel::Receiver<Event<'static, T>>,
+  events: EventReceiver<T>,
   /// Draw queue of EventLoop
   draws: crossbeam_channel::Receiver<WindowId>,
+  /// Windows whose scale factor changed
+  scale_factor_changes: crossbeam_channel::Receiver<(WindowId, f64)>,
+  /// Closures sent through `EventLoopProxy::run_on_main`
+  runs: crossbeam_channel::Receiver<MainThreadFn<T>>,
+  /// Sender of the closure queue for EventLoopProxy
+  run_tx: crossbeam_channel::Sender<MainThreadFn<T>>,
   /// Boolean to control device event thread
   run_device_thread: Option<Rc<AtomicBool>>,
+  /// Sources delivering the Unix signals requested with `with_signals`
+  signal_sources: Vec<glib::SourceId>,
+  /// Handlers emitting `Event::MonitorsChanged`
+  monitor_watch: Option<monitor::MonitorWatch<T>>,
+  /// Handler of `Xft.dpi` changes
+  xft_dpi_handler: Option<(gtk::Settings, glib::SignalHandlerId)>,
+  /// Where `pump_events` left off in the state machine
+  state: EventState,
+  /// Control flow carried between `pump_events` calls
+  control_flow: ControlFlow,
+  /// Whether `StartCause::Init` has been sent since the loop last exited
+  started: bool,
+}
+
+/// The states of the event loop, see [`EventLoop::run_return`].
+enum EventState {
+  NewStart,
+  EventQueue,
+  DrawQueue,
+}
+
+/// How long a requested redraw waits for a frame callback before the window is considered occluded.
+const FRAME_CALLBACK_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn send_occluded<T>(event_tx: &EventSender<T>, id: WindowId, occluded: bool) {
+  if let Err(e) = event_tx.send(Event::WindowEvent {
+    window_id: RootWindowId(id),
+    event: WindowEvent::Occluded(occluded),
+  }) {
+    log::warn!(
+      "Failed to send window occluded event to event channel: {}",
+      e
+    );
+  }
+}
+
+/// Reports the windows whose scale factor changed. GDK only notifies about its integer scale, not
+/// about `Xft.dpi` or the scale factor override changing as windows move between monitors.
+#[derive(Clone)]
+struct ScaleFactorTracker {
+  scale_factors: Rc<RefCell<HashMap<WindowId, f64>>>,
+  changes_tx: crossbeam_channel::Sender<(WindowId, f64)>,
+}
+
+impl ScaleFactorTracker {
+  fn track(&self, window: &impl IsA<gtk::Widget>, id: WindowId) {
+    self
+      .scale_factors
+      .borrow_mut()
+      .insert(id, scale::widget_scale_factor(window));
+  }
+
+  fn update(&self, window: &impl IsA<gtk::Widget>, id: WindowId) {
+    let mut scale_factors = self.scale_factors.borrow_mut();
+    let Some(last) = scale_factors.get_mut(&id) else {
+      return;
+    };
+    let scale_factor = scale::widget_scale_factor(window);
+    if *last != scale_factor {
+      *last = scale_factor;
+      if let Err(e) = self.changes_tx.send((id, scale_factor)) {
+        log::warn!("Failed to send scale factor change to event channel: {}", e);
+      }
+    }
+  }
+
+  fn forget(&self, id: WindowId) {
+    self.scale_factors.borrow_mut().remove(&id);
+  }
+}
+
+/// A redraw waiting for the frame clock of its window.
+struct PendingRedraw {
+  tick: gtk::TickCallbackId,
+  requested: Instant,
+}
+
+/// Delivers redraws of windows paced by their frame clock, so they follow the display's refresh
+/// rate.
+struct RedrawScheduler<T: 'static> {
+  /// Windows waiting for their frame clock to deliver a redraw.
+  pending: Rc<RefCell<HashMap<WindowId, PendingRedraw>>>,
+  /// Windows whose frame callback timeout is armed.
+  watched: Rc<RefCell<HashSet<WindowId>>>,
+  occlusion: util::OcclusionTracker,
+  event_tx: EventSender<T>,
+  draw_tx: crossbeam_channel::Sender<WindowId>,
+  is_wayland: bool,
+}
+
+impl<T> Clone for RedrawScheduler<T> {
+  fn clone(&self) -> Self {
+    Self {
+      pending: self.pending.clone(),
+      watched: self.watched.clone(),
+      occlusion: self.occlusion.clone(),
+      event_tx: self.event_tx.clone(),
+      draw_tx: self.draw_tx.clone(),
+      is_wayland: self.is_wayland,
+    }
+  }
+}
+
+impl<T> RedrawScheduler<T> {
+  fn request(&self, window: &impl IsA<gtk::Widget>, id: WindowId) {
+    // The frame clock only runs while the window is mapped and might not run while it is
+    // occluded, so deliver the redraw right away otherwise. Without frame callbacks alone, the
+    // redraw still waits for the frame clock, which resumes as soon as the compositor does.
+    if !window.is_mapped() || Self::skips_frame_clock(&self.occlusion, id) {
+      self.send(id);
+      return;
+    }
+    if self.pending.borrow().contains_key(&id) {
+      return;
+    }
+
+    let redraws = self.clone();
+    let tick = window.add_tick_callback(move |_, _| {
+      redraws.pending.borrow_mut().remove(&id);
+      if let Some(occluded) = redraws.occlusion.update(id, Occlusion::NO_FRAMES, false) {
+        send_occluded(&redraws.event_tx, id, occluded);
+      }
+      redraws.send(id);
+      glib::ControlFlow::Break
+    });
+    self.pending.borrow_mut().insert(
+      id,
+      PendingRedraw {
+        tick,
+        requested: Instant::now(),
+      },
+    );
+
+    if self.is_wayland && self.watched.borrow_mut().insert(id) {
+      self.watch_frame_callbacks(id);
+    }
+  }
+
+  /// Wayland compositors stop sending frame callbacks to hidden windows, which freezes the frame
+  /// clock until the window is shown again. A redraw still waiting when the timeout expires is
+  /// then delivered right away, so there is at most one per timeout until frames come back.
+  ///
+  /// The timeout keeps re-arming itself while the window has redraws pending.
+  fn watch_frame_callbacks(&self, id: WindowId) {
+    let redraws = self.clone();
+    glib::timeout_add_local(FRAME_CALLBACK_TIMEOUT, move || {
+      let mut pending = redraws.pending.borrow_mut();
+      let Some(redraw) = pending.get(&id) else {
+        redraws.watched.borrow_mut().remove(&id);
+        return glib::ControlFlow::Break;
+      };
+      if redraw.requested.elapsed() < FRAME_CALLBACK_TIMEOUT {
+        return glib::ControlFlow::Continue;
+      }
+
+      if let Some(redraw) = pending.remove(&id) {
+        redraw.tick.remove();
+      }
+      drop(pending);
+      if let Some(occluded) = redraws.occlusion.update(id, Occlusion::NO_FRAMES, true) {
+        send_occluded(&redraws.event_tx, id, occluded);
+      }
+      redraws.send(id);
+      glib::ControlFlow::Continue
+    });
+  }
+
+  /// Drops the pending redraw of a destroyed window.
+  fn forget(&self, id: WindowId) {
+    self.pending.borrow_mut().remove(&id);
+  }
+
+  /// Whether the window is occluded for another reason than missing frame callbacks, so its
+  /// frame clock might not run.
+  fn skips_frame_clock(occlusion: &util::OcclusionTracker, id: WindowId) -> bool {
+    !occlusion
+      .reasons(id)
+      .difference(Occlusion::NO_FRAMES)
+      .is_empty()
+  }
+
+  fn send(&self, id: WindowId) {
+    if let Err(e) = self.draw_tx.send(id) {
+      log::warn!("Failed to send redraw event to event channel: {}", e);
+    }
+  }
+}
+
+/// Drops repeated redraws of the same window, keeping the order in which they were requested.
+fn dedup_redraws(redraws: impl IntoIterator<Item = WindowId>) -> Vec<WindowId> {
+  let mut seen = HashSet::new();
+  redraws.into_iter().filter(|id| seen.insert(*id)).collect()
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+/// Runs one GTK main iteration, blocking for at most `timeout`, or until an event arrives if it is
+/// `None`.
+fn main_iteration(timeout: Option<Duration>) {
+  match timeout {
+    Some(timeout) if timeout.is_zero() => {
+      gtk::main_iteration_do(false);
+    }
+    Some(timeout) => {
+      let fired = Rc::new(Cell::new(false));
+      let source = {
+        let fired = fired.clone();
+        glib::timeout_add_local_once(timeout, move || fired.set(true))
+      };
+      gtk::main_iteration_do(true);
+      if !fired.get() {
+        source.remove();
+      }
+    }
+    None => {
+      gtk::main_iteration_do(true);
+    }
+  }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
 pub(crate) struct PlatformSpecificEventLoopAttributes {
   pub(crate) any_thread: bool,
   pub(crate) app_id: Option<String>,
+  pub(crate) synthetic_key_repeat: bool,
+  pub(crate) backend: Backend,
+  pub(crate) signals: Vec<i32>,
+  pub(crate) user_event_capacity: Option<NonZeroUsize>,
+  pub(crate) user_event_priority: UserEventPriority,
+  pub(crate) user_event_budget: Option<NonZeroUsize>,
+  pub(crate) relocate_orphaned_windows: bool,
+  pub(crate) scale_factor_override: Option<ScaleFactorOverride>,
+}
+
+/// The display server backend the event loop connects through.
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+  /// Let GDK decide, it tries Wayland first and falls back to X11. This respects `GDK_BACKEND`.
+  #[default]
+  Auto,
+  /// Connect to an X11 server, which is XWayland on Wayland sessions.
+  X11,
+  /// Connect to a Wayland compositor.
+  Wayland,
+}
+
+impl Backend {
+  /// Environment variable overriding the backend chosen with `EventLoopBuilderExtUnix::with_backend`.
+  const ENV_VAR: &str = "TAO_UNIX_BACKEND";
+
+  fn from_env() -> Result<Option<Self>, EventLoopError> {
+    let Some(value) = std::env::var_os(Self::ENV_VAR) else {
+      return Ok(None);
+    };
+    Self::parse(&value.to_string_lossy()).map(Some)
+  }
+
+  fn parse(value: &str) -> Result<Self, EventLoopError> {
+    match value.to_ascii_lowercase().as_str() {
+      "auto" => Ok(Backend::Auto),
+      "x11" => Ok(Backend::X11),
+      "wayland" => Ok(Backend::Wayland),
+      other => Err(EventLoopError::UnsupportedBackend(other.to_string())),
+    }
+  }
+
+  fn gdk_name(&self) -> Option<&'static str> {
+    match self {
+      Backend::Auto => None,
+      Backend::X11 => Some("x11"),
+      Backend::Wayland => Some("wayland"),
+    }
+  }
 }
 
 impl<T: 'static> EventLoop<T> {
@@ -206,25 +524,80 @@ impl<T: 'static> EventLoop<T> {
       assert_is_main_thread("new_any_thread");
     }
 
+    match Self::try_new(attrs) {
+      Ok(event_loop) => event_loop,
+      Err(e) => panic!("Failed to initialize gtk backend!: {e}"),
+    }
+  }
+
+  pub(crate) fn try_new(
+    attrs: &PlatformSpecificEventLoopAttributes,
+  ) -> Result<EventLoop<T>, EventLoopError> {
+    if !attrs.any_thread && !is_main_thread() {
+      return Err(EventLoopError::NotMainThread);
+    }
+
     let context = MainContext::default();
-    context
-      .with_thread_default(|| {
-        EventLoop::new_gtk(attrs.app_id.as_deref()).expect("Failed to initialize gtk backend!")
-      })
-      .expect("Failed to initialize gtk backend!")
+    match context.with_thread_default(|| EventLoop::new_gtk(attrs)) {
+      Ok(event_loop) => event_loop,
+      Err(e) => Err(EventLoopError::Os(os_error!(OsError::new(e.to_string())))),
+    }
   }
 
-  fn new_gtk(app_id: Option<&str>) -> Result<EventLoop<T>, Box<dyn Error>> {
+  fn new_gtk(attrs: &PlatformSpecificEventLoopAttributes) -> Result<EventLoop<T>, EventLoopError> {
+    let backend = Backend::from_env()?.unwrap_or(attrs.backend);
+    // Has to happen before gdk opens the display to have any effect, which it already did if the
+    // application or a previous event loop initialized GTK.
+    let initialized = gtk::is_initialized();
+    if let Some(name) = backend.gdk_name().filter(|_| !initialized) {
+      gdk::set_allowed_backends(name);
+    }
+
     // This should be done by gtk::Application::new, but does not work properly
-    gtk::init()?;
+    gtk::init().map_err(|e| {
+      // GTK is only left uninitialized when it couldn't open the display.
+      if gtk::is_initialized() {
+        return EventLoopError::Os(os_error!(OsError::new(e.to_string())));
+      }
+      match backend.gdk_name() {
+        Some(name) => EventLoopError::BackendUnavailable(name.to_string()),
+        None => EventLoopError::NoDisplay,
+      }
+    })?;
+    let display = gdk::Display::default().ok_or(EventLoopError::NoDisplay)?;
+    scale::set_override(attrs.scale_factor_override.clone());
+    let gdk_backend = display.backend();
+    if !gdk_backend.is_x11() && !gdk_backend.is_wayland() {
+      return Err(EventLoopError::UnsupportedBackend(
+        display.type_().name().to_string(),
+      ));
+    }
+    let connected = match backend {
+      Backend::Auto => true,
+      Backend::X11 => gdk_backend.is_x11(),
+      Backend::Wayland => gdk_backend.is_wayland(),
+    };
+    if !connected {
+      return Err(EventLoopError::BackendUnavailable(
+        backend.gdk_name().unwrap_or_default().to_string(),
+      ));
+    }
+
     let context = MainContext::default();
-    let app = gtk::Application::new(app_id, gio::ApplicationFlags::empty());
+    let app = gtk::Application::new(attrs.app_id.as_deref(), gio::ApplicationFlags::empty());
     let app_ = app.clone();
     let cancellable: Option<&Cancellable> = None;
-    app.register(cancellable)?;
+    app
+      .register(cancellable)
+      .map_err(|e| EventLoopError::Os(os_error!(OsError::new(e.to_string()))))?;
 
     // Send StartCause::Init event
-    let (event_tx, event_rx) = crossbeam_channel::unbounded();
+    let (event_tx, event_rx) = user_events::channel();
+    let user_events = Arc::new(UserEventQueue::new(
+      attrs.user_event_capacity,
+      attrs.user_event_priority,
+      &event_tx,
+    ));
     let (draw_tx, draw_rx) = crossbeam_channel::unbounded();
     let event_tx_ = event_tx.clone();
     app.connect_activate(move |_| {
@@ -232,22 +605,32 @@ impl<T: 'static> EventLoop<T> {
         log::warn!("Failed to send init event to event channel: {}", e);
       }
     });
-    let draw_tx_ = draw_tx.clone();
     let user_event_tx = event_tx.clone();
+    let (run_tx, run_rx) = crossbeam_channel::unbounded();
+    let signal_sources = sources::add_signals(&attrs.signals, &event_tx);
 
     // Create event loop window target.
     let (window_requests_tx, window_requests_rx) = glib::MainContext::channel(Priority::default());
-    let display = gdk::Display::default()
-      .expect("GdkDisplay not found. This usually means `gkt_init` hasn't called yet.");
+    let key_repeat_settings = keyboard::KeyRepeatSettingsCache::new(&display);
     let window_target = EventLoopWindowTarget {
       display,
       app,
       windows: Rc::new(RefCell::new(HashSet::new())),
       window_requests_tx,
-      draw_tx: draw_tx_,
+      event_tx: event_tx.clone(),
+      timers: Default::default(),
+      fd_watches: Default::default(),
+      key_repeat_settings,
       _marker: std::marker::PhantomData,
     };
 
+    let monitor_watch = monitor::watch_monitors(
+      &window_target.display,
+      &window_target.app,
+      event_tx.clone(),
+      attrs.relocate_orphaned_windows,
+    );
+
     // Spawn x11 thread to receive Device events.
     let run_device_thread = if window_target.is_x11() {
       let (device_tx, device_rx) = glib::MainContext::channel(glib::Priority::default());
@@ -275,6 +658,31 @@ impl<T: 'static> EventLoop<T> {
 
     let mut taskbar = TaskbarIndicator::new();
     let is_wayland = window_target.is_wayland();
+    let synthetic_key_repeat = attrs.synthetic_key_repeat;
+    let key_repeat_settings = window_target.key_repeat_settings.clone();
+    let occlusion = util::OcclusionTracker::default();
+    let (scale_factor_tx, scale_factor_rx) = crossbeam_channel::unbounded();
+    let scale_factors = ScaleFactorTracker {
+      scale_factors: Default::default(),
+      changes_tx: scale_factor_tx,
+    };
+    let scale_factors_ = scale_factors.clone();
+    let app = window_target.app.clone();
+    let xft_dpi_handler = scale::connect_xft_dpi_changed(&window_target.display, move || {
+      for window in app.windows() {
+        if let Some(window) = window.downcast_ref::<gtk::ApplicationWindow>() {
+          scale_factors_.update(window, WindowId(window.id()));
+        }
+      }
+    });
+    let redraws = RedrawScheduler {
+      pending: Default::default(),
+      watched: Default::default(),
+      occlusion: occlusion.clone(),
+      event_tx: event_tx.clone(),
+      draw_tx: draw_tx.clone(),
+      is_wayland,
+    };
 
     // Window Request
     window_requests_rx.attach(Some(&context), move |(id, request)| {
@@ -443,6 +851,7 @@ impl<T: 'static> EventLoop<T> {
               window.input_shape_combine_region(None)
             };
           }
+          WindowRequest::Redraw => redraws.request(&window, id),
           WindowRequest::ProgressBarState(_) => unreachable!(),
           WindowRequest::BadgeCount(_, _) => unreachable!(),
           WindowRequest::SetTheme(_) => unreachable!(),
@@ -450,7 +859,20 @@ impl<T: 'static> EventLoop<T> {
             transparent,
             fullscreen,
             cursor_moved,
+            motion_compression,
+            motion_history,
           } => {
+            if !motion_compression {
+              if let Some(gdk_window) = window.window() {
+                gdk_window.set_event_compression(false);
+              }
+              window.connect_realize(|window| {
+                if let Some(gdk_window) = window.window() {
+                  gdk_window.set_event_compression(false);
+                }
+              });
+            }
+
             window.add_events(
               EventMask::POINTER_MOTION_MASK
                 | EventMask::BUTTON1_MOTION_MASK
@@ -458,7 +880,9 @@ impl<T: 'static> EventLoop<T> {
                 | EventMask::TOUCH_MASK
                 | EventMask::STRUCTURE_MASK
                 | EventMask::FOCUS_CHANGE_MASK
-                | EventMask::SCROLL_MASK,
+                | EventMask::SCROLL_MASK
+                | EventMask::TOUCHPAD_GESTURE_MASK
+                | EventMask::VISIBILITY_NOTIFY_MASK,
             );
 
             let fullscreen = Rc::new(AtomicBool::new(fullscreen));
@@ -588,8 +1012,15 @@ impl<T: 'static> EventLoop<T> {
               glib::Propagation::Stop
             });
 
+            scale_factors.track(&window, id);
+            let scale_factors_ = scale_factors.clone();
+            window.connect_scale_factor_notify(move |window| scale_factors_.update(window, id));
+
             let tx_clone = event_tx.clone();
+            let scale_factors_ = scale_factors.clone();
             window.connect_configure_event(move |window, event| {
+              // Moving to another monitor can change the scale factor.
+              scale_factors_.update(window, id);
               let scale_factor = window.scale_factor();
 
               let (x, y) = window
@@ -621,7 +1052,9 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            let key_repeat_settings_ = key_repeat_settings.clone();
             window.connect_focus_in_event(move |_, _| {
+              key_repeat_settings_.refresh();
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Focused(true),
@@ -649,7 +1082,13 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            let occlusion_ = occlusion.clone();
+            let scale_factors_ = scale_factors.clone();
+            let redraws_ = redraws.clone();
             window.connect_destroy(move |_| {
+              occlusion_.remove(id);
+              scale_factors_.forget(id);
+              redraws_.forget(id);
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Destroyed,
@@ -678,10 +1117,51 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            let last_motion_time = Cell::new(None);
             window.connect_motion_notify_event(move |window, motion| {
+              if let Some(pen) = util::pen_event(
+                motion,
+                TouchPhase::Moved,
+                motion.state(),
+                window.scale_factor() as f64,
+              ) {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Pen(pen),
+                }) {
+                  log::warn!("Failed to send pen event to event channel: {}", e);
+                }
+              }
+
               if cursor_moved {
                 if let Some(cursor) = motion.device() {
                   let scale_factor = window.scale_factor();
+
+                  // Send the samples GDK compressed since the previous motion event first.
+                  let time = motion.time();
+                  if let (true, Some(since), Some(gdk_window)) = (
+                    motion_history,
+                    last_motion_time.replace(Some(time)),
+                    window.window(),
+                  ) {
+                    for (x, y, sample_time) in
+                      util::motion_history(&cursor, &gdk_window, since, time)
+                    {
+                      if let Err(e) = tx_clone.send(Event::WindowEvent {
+                        window_id: RootWindowId(id),
+                        event: WindowEvent::CursorMoved {
+                          position: LogicalPosition::new(x, y).to_physical(scale_factor as f64),
+                          device_id: DEVICE_ID,
+                          // this field is depracted so it is fine to pass empty state
+                          modifiers: ModifiersState::empty(),
+                          timestamp: util::event_time(sample_time),
+                        },
+                      }) {
+                        log::warn!("Failed to send cursor moved event to event channel: {}", e);
+                      }
+                    }
+                  }
+
                   let (_, x, y) = cursor.window_at_position();
                   if let Err(e) = tx_clone.send(Event::WindowEvent {
                     window_id: RootWindowId(id),
@@ -690,6 +1170,7 @@ impl<T: 'static> EventLoop<T> {
                       device_id: DEVICE_ID,
                       // this field is depracted so it is fine to pass empty state
                       modifiers: ModifiersState::empty(),
+                      timestamp: util::event_time(time),
                     },
                   }) {
                     log::warn!("Failed to send cursor moved event to event channel: {}", e);
@@ -712,22 +1193,44 @@ impl<T: 'static> EventLoop<T> {
               glib::Propagation::Proceed
             });
 
+            let click_counter = Rc::new(RefCell::new(util::ClickCounter::new()));
+
             let tx_clone = event_tx.clone();
-            window.connect_button_press_event(move |_, event| {
+            let click_counter_ = click_counter.clone();
+            window.connect_button_press_event(move |window, event| {
+              // GTK follows the second and third press of a multi-click with `2BUTTON_PRESS` and
+              // `3BUTTON_PRESS` events, the click count already covers them.
+              if event.event_type() != gdk::EventType::ButtonPress {
+                return glib::Propagation::Stop;
+              }
+
               let button = event.button();
+              let (phase, buttons) = util::pen_button_change(button, event.state(), true);
+              if let Some(pen) =
+                util::pen_event(event, phase, buttons, window.scale_factor() as f64)
+              {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Pen(pen),
+                }) {
+                  log::warn!("Failed to send pen event to event channel: {}", e);
+                }
+              }
+
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseInput {
-                  button: match button {
-                    1 => MouseButton::Left,
-                    2 => MouseButton::Middle,
-                    3 => MouseButton::Right,
-                    _ => MouseButton::Other(button as u16),
-                  },
+                  button: util::mouse_button(button),
                   state: ElementState::Pressed,
                   device_id: DEVICE_ID,
                   // this field is depracted so it is fine to pass empty state
                   modifiers: ModifiersState::empty(),
+                  click_count: click_counter_.borrow_mut().press(
+                    button,
+                    event.time(),
+                    event.position(),
+                  ),
+                  timestamp: util::event_time(event.time()),
                 },
               }) {
                 log::warn!(
@@ -739,21 +1242,30 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_button_release_event(move |_, event| {
+            window.connect_button_release_event(move |window, event| {
               let button = event.button();
+              let (phase, buttons) = util::pen_button_change(button, event.state(), false);
+              if let Some(pen) =
+                util::pen_event(event, phase, buttons, window.scale_factor() as f64)
+              {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Pen(pen),
+                }) {
+                  log::warn!("Failed to send pen event to event channel: {}", e);
+                }
+              }
+
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseInput {
-                  button: match button {
-                    1 => MouseButton::Left,
-                    2 => MouseButton::Middle,
-                    3 => MouseButton::Right,
-                    _ => MouseButton::Other(button as u16),
-                  },
+                  button: util::mouse_button(button),
                   state: ElementState::Released,
                   device_id: DEVICE_ID,
                   // this field is depracted so it is fine to pass empty state
                   modifiers: ModifiersState::empty(),
+                  click_count: click_counter.borrow().release(button),
+                  timestamp: util::event_time(event.time()),
                 },
               }) {
                 log::warn!(
@@ -777,6 +1289,7 @@ impl<T: 'static> EventLoop<T> {
                     _ => TouchPhase::Ended,
                   },
                   modifiers: ModifiersState::empty(),
+                  timestamp: util::event_time(event.time()),
                 },
               }) {
                 log::warn!("Failed to send scroll event to event channel: {}", e);
@@ -785,47 +1298,89 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            let keyboard_handler = Rc::new(move |event_key: EventKey, element_state| {
-              // if we have a modifier lets send it
-              let mut mods = keyboard::get_modifiers(event_key.clone());
-              if !mods.is_empty() {
-                // if we release the modifier tell the world
-                if ElementState::Released == element_state {
-                  mods = ModifiersState::empty();
+            let pinch_deltas = util::PinchDeltas::default();
+            window.connect_event(move |window, event| {
+              let scale_factor = window.scale_factor() as f64;
+              let event = if let Some(pinch) = event.downcast_ref::<gdk::EventTouchpadPinch>() {
+                let phase = util::pinch_phase(pinch);
+                let (scale_delta, rotation_delta) =
+                  pinch_deltas.update(phase, pinch.scale(), pinch.angle_delta());
+                WindowEvent::TouchpadPinch {
+                  device_id: DEVICE_ID,
+                  phase,
+                  scale_delta,
+                  rotation_delta,
+                  fingers: pinch.n_fingers() as u32,
+                  timestamp: util::event_time(pinch.time()),
                 }
-
-                if let Err(e) = tx_clone.send(Event::WindowEvent {
-                  window_id: RootWindowId(id),
-                  event: WindowEvent::ModifiersChanged(mods),
-                }) {
-                  log::warn!(
-                    "Failed to send modifiers changed event to event channel: {}",
-                    e
-                  );
-                } else {
-                  // stop here we don't want to send the key event
-                  // as we emit the `ModifiersChanged`
-                  return glib::ControlFlow::Continue;
+              } else if let Some(swipe) = event.downcast_ref::<gdk::EventTouchpadSwipe>() {
+                let (dx, dy) = swipe.delta();
+                WindowEvent::TouchpadSwipe {
+                  device_id: DEVICE_ID,
+                  phase: util::swipe_phase(swipe),
+                  delta: LogicalPosition::new(dx, dy).to_physical(scale_factor),
+                  fingers: swipe.n_fingers() as u32,
+                  timestamp: util::event_time(swipe.time()),
                 }
+              } else {
+                return glib::Propagation::Proceed;
+              };
+              if let Err(e) = tx_clone.send(Event::WindowEvent {
+                window_id: RootWindowId(id),
+                event,
+              }) {
+                log::warn!(
+                  "Failed to send touchpad gesture event to event channel: {}",
+                  e
+                );
               }
+              glib::Propagation::Stop
+            });
 
-              // todo: implement repeat?
-              let event = keyboard::make_key_event(&event_key, false, None, element_state);
+            let tx_clone = event_tx.clone();
+            let keyboard_handler = Rc::new(
+              move |event_key: EventKey, element_state, is_repeat, timestamp: Instant| {
+                // if we have a modifier lets send it
+                let mut mods = keyboard::get_modifiers(event_key.clone());
+                if !mods.is_empty() {
+                  // if we release the modifier tell the world
+                  if ElementState::Released == element_state {
+                    mods = ModifiersState::empty();
+                  }
 
-              if let Some(event) = event {
-                if let Err(e) = tx_clone.send(Event::WindowEvent {
-                  window_id: RootWindowId(id),
-                  event: WindowEvent::KeyboardInput {
-                    device_id: DEVICE_ID,
-                    event,
-                    is_synthetic: false,
-                  },
-                }) {
-                  log::warn!("Failed to send keyboard event to event channel: {}", e);
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::ModifiersChanged(mods),
+                  }) {
+                    log::warn!(
+                      "Failed to send modifiers changed event to event channel: {}",
+                      e
+                    );
+                  } else {
+                    // stop here we don't want to send the key event
+                    // as we emit the `ModifiersChanged`
+                    return glib::ControlFlow::Continue;
+                  }
                 }
-              }
-              glib::ControlFlow::Continue
-            });
+
+                let event = keyboard::make_key_event(&event_key, is_repeat, None, element_state);
+
+                if let Some(event) = event {
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::KeyboardInput {
+                      device_id: DEVICE_ID,
+                      event,
+                      is_synthetic: false,
+                      timestamp,
+                    },
+                  }) {
+                    log::warn!("Failed to send keyboard event to event channel: {}", e);
+                  }
+                }
+                glib::ControlFlow::Continue
+              },
+            );
 
             let tx_clone = event_tx.clone();
             // TODO Add actual IME from system
@@ -844,23 +1399,108 @@ impl<T: 'static> EventLoop<T> {
               }
             });
 
+            let key_repeat = Rc::new(RefCell::new(keyboard::KeyRepeatState::default()));
+
             let handler = keyboard_handler.clone();
+            let key_repeat_ = key_repeat.clone();
+            let key_repeat_settings_ = key_repeat_settings.clone();
             window.connect_key_press_event(move |_, event_key| {
-              handler(event_key.to_owned(), ElementState::Pressed);
+              let keycode = event_key.hardware_keycode();
+              let is_repeat = key_repeat_.borrow_mut().press(keycode);
+              if synthetic_key_repeat {
+                // Repeats are generated by us, drop the ones coming from the OS.
+                if is_repeat {
+                  return glib::Propagation::Proceed;
+                }
+
+                let settings = key_repeat_settings_.get();
+                let handler = handler.clone();
+                let ime = ime.clone();
+                let event_key = event_key.to_owned();
+                keyboard::start_synthetic_repeat(&key_repeat_, keycode, settings, move || {
+                  handler(
+                    event_key.clone(),
+                    ElementState::Pressed,
+                    true,
+                    Instant::now(),
+                  );
+                  ime.filter_keypress(&event_key);
+                });
+              }
+
+              handler(
+                event_key.to_owned(),
+                ElementState::Pressed,
+                is_repeat,
+                util::event_time(event_key.time()),
+              );
               ime.filter_keypress(event_key);
 
               glib::Propagation::Proceed
             });
 
             let handler = keyboard_handler.clone();
+            let key_repeat_ = key_repeat.clone();
             window.connect_key_release_event(move |_, event_key| {
-              handler(event_key.to_owned(), ElementState::Released);
+              key_repeat_
+                .borrow_mut()
+                .release(event_key.hardware_keycode());
+              handler(
+                event_key.to_owned(),
+                ElementState::Released,
+                false,
+                util::event_time(event_key.time()),
+              );
               glib::Propagation::Proceed
             });
 
+            // Held keys won't be released while we are unfocused.
+            let key_repeat_ = key_repeat.clone();
+            window.connect_focus_out_event(move |_, _| {
+              key_repeat_.borrow_mut().clear();
+              glib::Propagation::Proceed
+            });
+
+            // Nor once the window is gone, so don't keep repeating into it.
+            let key_repeat_ = key_repeat.clone();
+            window.connect_unrealize(move |_| {
+              key_repeat_.borrow_mut().clear();
+            });
+            window.connect_destroy(move |_| {
+              key_repeat.borrow_mut().clear();
+            });
+
             let tx_clone = event_tx.clone();
+            let occlusion_ = occlusion.clone();
             window.connect_window_state_event(move |window, event| {
               let state = event.changed_mask();
+              if let Some(window_state) = util::window_state_change(state, event.new_window_state())
+              {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::StateChanged(window_state),
+                }) {
+                  log::warn!(
+                    "Failed to send window state changed event to event channel: {}",
+                    e
+                  );
+                }
+              }
+
+              for (flag, reason) in [
+                (WindowState::ICONIFIED, Occlusion::MINIMIZED),
+                (WindowState::WITHDRAWN, Occlusion::WITHDRAWN),
+              ] {
+                if state.contains(flag) {
+                  // Hiding the window also withdraws it, which is not an occlusion.
+                  let applies = event.new_window_state().contains(flag)
+                    && (flag != WindowState::WITHDRAWN || window.is_visible());
+                  if let Some(occluded) = occlusion_.update(id, reason, applies) {
+                    send_occluded(&tx_clone, id, occluded);
+                  }
+                }
+              }
+
               if state.contains(WindowState::ICONIFIED) || state.contains(WindowState::MAXIMIZED) {
                 let scale_factor = window.scale_factor();
 
@@ -890,11 +1530,25 @@ impl<T: 'static> EventLoop<T> {
               glib::Propagation::Proceed
             });
 
-            // Receive draw events of the window.
-            let draw_clone = draw_tx.clone();
+            let tx_clone = event_tx.clone();
+            let occlusion_ = occlusion.clone();
+            window.connect_event(move |_, event| {
+              if let Some(visibility) = event.downcast_ref::<gdk::EventVisibility>() {
+                let obscured = visibility.state() == gdk::VisibilityState::FullyObscured;
+                if let Some(occluded) = occlusion_.update(id, Occlusion::OBSCURED, obscured) {
+                  send_occluded(&tx_clone, id, occluded);
+                }
+              }
+              glib::Propagation::Proceed
+            });
+
+            // Receive draw events of the window, GTK already paces them with the frame clock.
+            // Occluded windows are only redrawn on request.
+            let redraws_ = redraws.clone();
+            let occlusion_ = occlusion.clone();
             window.connect_draw(move |window, cr| {
-              if let Err(e) = draw_clone.send(id) {
-                log::warn!("Failed to send redraw event to event channel: {}", e);
+              if !RedrawScheduler::<T>::skips_frame_clock(&occlusion_, id) {
+                redraws_.send(id);
               }
 
               if transparent {
@@ -957,10 +1611,21 @@ impl<T: 'static> EventLoop<T> {
         p: window_target,
         _marker: std::marker::PhantomData,
       },
-      user_event_tx,
+      user_events,
+      user_event_budget: attrs.user_event_budget,
+      delivered_user_events: 0,
       events: event_rx,
       draws: draw_rx,
+      scale_factor_changes: scale_factor_rx,
+      runs: run_rx,
+      run_tx,
       run_device_thread,
+      signal_sources,
+      monitor_watch: Some(monitor_watch),
+      xft_dpi_handler,
+      state: EventState::NewStart,
+      control_flow: ControlFlow::default(),
+      started: false,
     };
 
     Ok(event_loop)
@@ -1008,125 +1673,222 @@ impl<T: 'static> EventLoop<T> {
   where
     F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
   {
-    enum EventState {
-      NewStart,
-      EventQueue,
-      DrawQueue,
+    // Every call to `run_return` starts over with a fresh control flow and `StartCause::Init`.
+    self.started = false;
+    loop {
+      if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+        break code;
+      }
     }
+  }
 
+  /// Runs the state machine described in [`EventLoop::run_return`] until it gets back to
+  /// `NewStart` or the loop exits. The state and control flow are kept between calls, so only the
+  /// first call (or the first one after an exit) sends `StartCause::Init`.
+  ///
+  /// When the control flow asks to wait, this blocks for at most `timeout`, or until events arrive
+  /// if `timeout` is `None`.
+  pub(crate) fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
     let context = MainContext::default();
-    let run_device_thread = self.run_device_thread.clone();
 
     context
       .with_thread_default(|| {
-        let mut control_flow = ControlFlow::default();
-        let window_target = &self.window_target;
-        let events = &self.events;
-        let draws = &self.draws;
-
-        window_target.p.app.activate();
-
-        let mut state = EventState::NewStart;
-        let exit_code = loop {
-          let mut blocking = false;
-          match state {
-            EventState::NewStart => match control_flow {
-              ControlFlow::ExitWithCode(code) => {
-                callback(Event::LoopDestroyed, window_target, &mut control_flow);
-                break code;
-              }
-              ControlFlow::Wait => {
-                if !events.is_empty() {
-                  callback(
-                    Event::NewEvents(StartCause::WaitCancelled {
-                      start: Instant::now(),
-                      requested_resume: None,
-                    }),
-                    window_target,
-                    &mut control_flow,
-                  );
-                  state = EventState::EventQueue;
-                } else {
-                  blocking = true;
-                }
-              }
-              ControlFlow::WaitUntil(requested_resume) => {
-                let start = Instant::now();
-                if start >= requested_resume {
-                  callback(
-                    Event::NewEvents(StartCause::ResumeTimeReached {
-                      start,
-                      requested_resume,
-                    }),
-                    window_target,
-                    &mut control_flow,
-                  );
-                  state = EventState::EventQueue;
-                } else if !events.is_empty() {
-                  callback(
-                    Event::NewEvents(StartCause::WaitCancelled {
-                      start,
-                      requested_resume: Some(requested_resume),
-                    }),
-                    window_target,
-                    &mut control_flow,
-                  );
-                  state = EventState::EventQueue;
-                } else {
-                  blocking = true;
-                }
-              }
-              _ => {
-                callback(
-                  Event::NewEvents(StartCause::Poll),
-                  window_target,
-                  &mut control_flow,
-                );
-                state = EventState::EventQueue;
-              }
-            },
-            EventState::EventQueue => match control_flow {
-              ControlFlow::ExitWithCode(code) => {
-                callback(Event::LoopDestroyed, window_target, &mut control_flow);
-                break (code);
+        if !self.started {
+          self.started = true;
+          self.control_flow = ControlFlow::default();
+          self.state = EventState::NewStart;
+          self.delivered_user_events = 0;
+          self.window_target.p.app.activate();
+        }
+
+        let status = self.iterate(timeout, &mut callback);
+        if let PumpStatus::Exit(_) = status {
+          self.started = false;
+        }
+        status
+      })
+      .unwrap_or(PumpStatus::Exit(1))
+  }
+
+  fn iterate<F>(&mut self, timeout: Option<Duration>, callback: &mut F) -> PumpStatus
+  where
+    F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+  {
+    let window_target = &self.window_target;
+    let events = &self.events;
+    let draws = &self.draws;
+    let scale_factor_changes = &self.scale_factor_changes;
+    let runs = &self.runs;
+    let user_events = &self.user_events;
+    let user_event_budget = self.user_event_budget;
+    let delivered_user_events = &mut self.delivered_user_events;
+    // User events held back by the budget count as pending, so the next iteration starts right
+    // away to deliver them. The budget is reset at the end of every iteration, so this can't spin.
+    let pending = || {
+      !events.is_empty()
+        || !user_events.is_empty()
+        || !draws.is_empty()
+        || !runs.is_empty()
+        || !scale_factor_changes.is_empty()
+    };
+    let control_flow = &mut self.control_flow;
+    let state = &mut self.state;
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+      // `None` means the state moved on, `Some(None)` means wait for events without a limit.
+      let mut wait = None;
+      let mut cleared = false;
+      match state {
+        EventState::NewStart => match *control_flow {
+          ControlFlow::ExitWithCode(code) => {
+            callback(Event::LoopDestroyed, window_target, control_flow);
+            return PumpStatus::Exit(code);
+          }
+          ControlFlow::Wait => {
+            if pending() {
+              callback(
+                Event::NewEvents(StartCause::WaitCancelled {
+                  start: Instant::now(),
+                  requested_resume: None,
+                }),
+                window_target,
+                control_flow,
+              );
+              *state = EventState::EventQueue;
+            } else {
+              wait = Some(None);
+            }
+          }
+          ControlFlow::WaitUntil(requested_resume) => {
+            let start = Instant::now();
+            if start >= requested_resume {
+              callback(
+                Event::NewEvents(StartCause::ResumeTimeReached {
+                  start,
+                  requested_resume,
+                }),
+                window_target,
+                control_flow,
+              );
+              *state = EventState::EventQueue;
+            } else if pending() {
+              callback(
+                Event::NewEvents(StartCause::WaitCancelled {
+                  start,
+                  requested_resume: Some(requested_resume),
+                }),
+                window_target,
+                control_flow,
+              );
+              *state = EventState::EventQueue;
+            } else {
+              wait = Some(Some(requested_resume - start));
+            }
+          }
+          _ => {
+            callback(
+              Event::NewEvents(StartCause::Poll),
+              window_target,
+              control_flow,
+            );
+            *state = EventState::EventQueue;
+          }
+        },
+        EventState::EventQueue => match *control_flow {
+          ControlFlow::ExitWithCode(code) => {
+            callback(Event::LoopDestroyed, window_target, control_flow);
+            return PumpStatus::Exit(code);
+          }
+          _ => {
+            // User events over the budget are left for the next iteration.
+            let mut pop_user_event = |priority| {
+              if user_event_budget.is_some_and(|budget| *delivered_user_events >= budget.get()) {
+                return None;
               }
-              _ => match events.try_recv() {
-                Ok(event) => match event {
-                  Event::LoopDestroyed => control_flow = ControlFlow::ExitWithCode(1),
-                  _ => callback(event, window_target, &mut control_flow),
+              let event = user_events.pop(priority, events.next_sequence())?;
+              *delivered_user_events += 1;
+              Some(event)
+            };
+
+            if let Some(event) = pop_user_event(UserEventPriority::High) {
+              callback(Event::UserEvent(event), window_target, control_flow);
+            } else if let Ok((id, scale_factor)) = scale_factor_changes.try_recv() {
+              scale_factor_changed(id, scale_factor, window_target, control_flow, callback);
+            } else if let Some(event) = pop_user_event(UserEventPriority::Normal) {
+              callback(Event::UserEvent(event), window_target, control_flow);
+            } else {
+              match events.try_recv() {
+                Some(event) => match event {
+                  Event::LoopDestroyed => *control_flow = ControlFlow::ExitWithCode(1),
+                  Event::FdReady { watch_id, .. } => {
+                    callback(event, window_target, control_flow);
+                    sources::rearm_fd_watch(&window_target.p.fd_watches, watch_id);
+                  }
+                  _ => callback(event, window_target, control_flow),
+                },
+                None => match pop_user_event(UserEventPriority::Low) {
+                  Some(event) => callback(Event::UserEvent(event), window_target, control_flow),
+                  None if !runs.is_empty() => {
+                    for run in runs.try_iter() {
+                      run(window_target);
+                    }
+                  }
+                  None => {
+                    callback(Event::MainEventsCleared, window_target, control_flow);
+                    *state = EventState::DrawQueue;
+                  }
                 },
-                Err(_) => {
-                  callback(Event::MainEventsCleared, window_target, &mut control_flow);
-                  state = EventState::DrawQueue;
-                }
-              },
-            },
-            EventState::DrawQueue => match control_flow {
-              ControlFlow::ExitWithCode(code) => {
-                callback(Event::LoopDestroyed, window_target, &mut control_flow);
-                break code;
-              }
-              _ => {
-                if let Ok(id) = draws.try_recv() {
-                  callback(
-                    Event::RedrawRequested(RootWindowId(id)),
-                    window_target,
-                    &mut control_flow,
-                  );
-                }
-                callback(Event::RedrawEventsCleared, window_target, &mut control_flow);
-                state = EventState::NewStart;
               }
-            },
+            }
+          }
+        },
+        EventState::DrawQueue => match *control_flow {
+          ControlFlow::ExitWithCode(code) => {
+            callback(Event::LoopDestroyed, window_target, control_flow);
+            return PumpStatus::Exit(code);
+          }
+          _ => {
+            // Deliver every window that needs a redraw at once, but only once per iteration.
+            for id in dedup_redraws(draws.try_iter()) {
+              callback(
+                Event::RedrawRequested(RootWindowId(id)),
+                window_target,
+                control_flow,
+              );
+            }
+            callback(Event::RedrawEventsCleared, window_target, control_flow);
+            *state = EventState::NewStart;
+            *delivered_user_events = 0;
+            cleared = true;
+          }
+        },
+      }
+
+      match wait {
+        Some(wait) => {
+          // Never block past the caller's deadline.
+          let remaining =
+            deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+          let limit = match (wait, remaining) {
+            (Some(wait), Some(remaining)) => Some(wait.min(remaining)),
+            (wait, remaining) => wait.or(remaining),
+          };
+          main_iteration(limit);
+          if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return PumpStatus::Continue;
           }
-          gtk::main_iteration_do(blocking);
-        };
-        if let Some(run_device_thread) = run_device_thread {
-          run_device_thread.store(false, Ordering::Relaxed);
         }
-        exit_code
-      })
-      .unwrap_or(1)
+        None => main_iteration(Some(Duration::ZERO)),
+      }
+
+      if cleared {
+        return PumpStatus::Continue;
+      }
+    }
   }
 
   #[inline]
@@ -1137,7 +1899,8 @@ impl<T: 'static> EventLoop<T> {
   /// Creates an `EventLoopProxy` that can be used to dispatch user events to the main event loop.
   pub fn create_proxy(&self) -> EventLoopProxy<T> {
     EventLoopProxy {
-      user_event_tx: self.user_event_tx.clone(),
+      user_events: self.user_events.clone(),
+      run_tx: self.run_tx.clone(),
     }
   }
 }
@@ -1145,13 +1908,15 @@ impl<T: 'static> EventLoop<T> {
 /// Used to send custom events to `EventLoop`.
 #[derive(Debug)]
 pub struct EventLoopProxy<T: 'static> {
-  user_event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+  user_events: Arc<UserEventQueue<T>>,
+  run_tx: crossbeam_channel::Sender<MainThreadFn<T>>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
   fn clone(&self) -> Self {
     Self {
-      user_event_tx: self.user_event_tx.clone(),
+      user_events: self.user_events.clone(),
+      run_tx: self.run_tx.clone(),
     }
   }
 }
@@ -1164,20 +1929,183 @@ impl<T: 'static> EventLoopProxy<T> {
   /// Returns an `Err` if the associated `EventLoop` no longer exists.
   pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
     self
-      .user_event_tx
-      .send(Event::UserEvent(event))
-      .map_err(|SendError(event)| {
-        if let Event::UserEvent(error) = event {
-          EventLoopClosed(error)
-        } else {
-          unreachable!();
-        }
-      })?;
+      .user_events
+      .send(self.user_events.default_priority(), event)
+  }
 
-    let context = MainContext::default();
-    context.wakeup();
+  /// Like `send_event`, but with the given priority instead of the event loop's default one.
+  pub fn send_event_with_priority(
+    &self,
+    event: T,
+    priority: UserEventPriority,
+  ) -> Result<(), EventLoopClosed<T>> {
+    self.user_events.send(priority, event)
+  }
+
+  /// Like `send_event`, but returns `TrySendError::Full` instead of blocking when the queue is
+  /// full.
+  pub fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+    self
+      .user_events
+      .try_send(None, self.user_events.default_priority(), event)
+  }
+
+  /// Like `send_event`, but waits asynchronously for space in the queue.
+  pub fn send_event_async(&self, event: T) -> SendEvent<T> {
+    SendEvent::new(
+      self.user_events.clone(),
+      self.user_events.default_priority(),
+      event,
+    )
+  }
+
+  /// Like `try_send_event`, but replaces the pending event sent with the same `key`, if any.
+  pub fn send_event_coalesced(&self, key: u64, event: T) -> Result<(), TrySendError<T>> {
+    self
+      .user_events
+      .try_send(Some(key), self.user_events.default_priority(), event)
+  }
+
+  /// Runs `f` on the event loop thread during the next iteration and resolves to its result.
+  pub fn run_on_main<F, R>(&self, f: F) -> RunOnMain<R>
+  where
+    F: FnOnce(&RootELW<T>) -> R + Send + 'static,
+    R: Send + 'static,
+  {
+    let shared = Arc::new(Mutex::new(RunOnMainState {
+      result: None,
+      waker: None,
+      closed: false,
+    }));
+    let completer = RunOnMainCompleter {
+      shared: shared.clone(),
+    };
+    // If the loop is gone the closure is dropped along with the completer, which closes the future.
+    let _ = self
+      .run_tx
+      .send(Box::new(move |target| completer.complete(f(target))));
+    MainContext::default().wakeup();
+
+    RunOnMain { shared }
+  }
+}
 
-    Ok(())
+type MainThreadFn<T> = Box<dyn FnOnce(&RootELW<T>) + Send>;
+
+struct RunOnMainState<R> {
+  result: Option<R>,
+  waker: Option<Waker>,
+  closed: bool,
+}
+
+/// Held by the closure sent to the event loop, closes the future when dropped without a result.
+struct RunOnMainCompleter<R> {
+  shared: Arc<Mutex<RunOnMainState<R>>>,
+}
+
+impl<R> RunOnMainCompleter<R> {
+  fn complete(self, result: R) {
+    self.shared.lock().unwrap().result = Some(result);
+  }
+}
+
+impl<R> Drop for RunOnMainCompleter<R> {
+  fn drop(&mut self) {
+    let mut state = self.shared.lock().unwrap();
+    state.closed = true;
+    if let Some(waker) = state.waker.take() {
+      waker.wake();
+    }
+  }
+}
+
+/// The future returned by `EventLoopProxyExtUnix::run_on_main`.
+///
+/// Resolves to `Err` if the event loop was dropped before running the closure.
+#[must_use = "futures do nothing unless polled"]
+pub struct RunOnMain<R> {
+  shared: Arc<Mutex<RunOnMainState<R>>>,
+}
+
+impl<R> Future for RunOnMain<R> {
+  type Output = Result<R, EventLoopClosed<()>>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut state = self.shared.lock().unwrap();
+    if let Some(result) = state.result.take() {
+      Poll::Ready(Ok(result))
+    } else if state.closed {
+      Poll::Ready(Err(EventLoopClosed(())))
+    } else {
+      state.waker = Some(cx.waker().clone());
+      Poll::Pending
+    }
+  }
+}
+
+impl<R> std::fmt::Debug for RunOnMain<R> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RunOnMain").finish_non_exhaustive()
+  }
+}
+
+impl<T: 'static> Drop for EventLoop<T> {
+  fn drop(&mut self) {
+    self.user_events.close();
+    sources::remove_fd_watches(&self.window_target.p.fd_watches);
+    sources::remove_timers(&self.window_target.p.timers);
+    for source in self.signal_sources.drain(..) {
+      source.remove();
+    }
+    if let Some(monitor_watch) = self.monitor_watch.take() {
+      monitor_watch.stop();
+    }
+    if let Some((settings, handler)) = self.xft_dpi_handler.take() {
+      settings.disconnect(handler);
+    }
+    // The loop can be pumped again after it exited, so the device thread only stops here.
+    if let Some(run_device_thread) = &self.run_device_thread {
+      run_device_thread.store(false, Ordering::Relaxed);
+    }
+  }
+}
+
+/// Delivers `ScaleFactorChanged` for the window `id`, then resizes it if the callback changed
+/// `new_inner_size`.
+fn scale_factor_changed<T, F>(
+  id: WindowId,
+  scale_factor: f64,
+  window_target: &RootELW<T>,
+  control_flow: &mut ControlFlow,
+  callback: &mut F,
+) where
+  F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+{
+  let Some(window) = window_target.p.app.window_by_id(id.0) else {
+    return;
+  };
+  // GDK keeps the physical size of the window, only the integer part of the scale is its own.
+  let gdk_scale = window.scale_factor();
+  let (width, height) = window.size();
+  let inner_size = LogicalSize::new(width, height).to_physical(gdk_scale as f64);
+  let mut new_inner_size = inner_size;
+
+  callback(
+    Event::WindowEvent {
+      window_id: RootWindowId(id),
+      event: WindowEvent::ScaleFactorChanged {
+        scale_factor,
+        new_inner_size: &mut new_inner_size,
+      },
+    },
+    window_target,
+    control_flow,
+  );
+
+  if new_inner_size != inner_size {
+    let (width, height) =
+      scale::size_to_gdk::<i32>(new_inner_size.into(), scale_factor, gdk_scale).into();
+    window.resize(width, height);
   }
 }
 
@@ -1278,3 +2206,78 @@ impl ResizeDirection {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{dedup_redraws, Backend, RunOnMain, RunOnMainCompleter, RunOnMainState, WindowId};
+  use crate::{error::EventLoopError, event_loop::EventLoopClosed};
+  use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+      atomic::{AtomicBool, Ordering},
+      Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+  };
+
+  struct Woken(AtomicBool);
+
+  impl Wake for Woken {
+    fn wake(self: Arc<Self>) {
+      self.0.store(true, Ordering::SeqCst);
+    }
+  }
+
+  fn run_on_main<R>() -> (RunOnMainCompleter<R>, RunOnMain<R>) {
+    let shared = Arc::new(Mutex::new(RunOnMainState {
+      result: None,
+      waker: None,
+      closed: false,
+    }));
+    (
+      RunOnMainCompleter {
+        shared: shared.clone(),
+      },
+      RunOnMain { shared },
+    )
+  }
+
+  #[test]
+  fn run_on_main_resolves() {
+    let woken = Arc::new(Woken(AtomicBool::new(false)));
+    let waker = Waker::from(woken.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let (completer, mut future) = run_on_main();
+    assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+    completer.complete(7);
+    assert!(woken.0.load(Ordering::SeqCst));
+    assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(Ok(7)));
+
+    // The closure was dropped without running, e.g. with the event loop.
+    let (completer, mut future) = run_on_main::<u32>();
+    drop(completer);
+    assert_eq!(
+      Pin::new(&mut future).poll(&mut cx),
+      Poll::Ready(Err(EventLoopClosed(())))
+    );
+  }
+
+  #[test]
+  fn dedup_redraws_keeps_order() {
+    let ids = [3, 1, 3, 2, 1].map(WindowId);
+    assert_eq!(dedup_redraws(ids), [3, 1, 2].map(WindowId));
+  }
+
+  #[test]
+  fn parse_backend() {
+    assert_eq!(Backend::parse("auto").unwrap(), Backend::Auto);
+    assert_eq!(Backend::parse("X11").unwrap(), Backend::X11);
+    assert_eq!(Backend::parse("wayland").unwrap(), Backend::Wayland);
+    assert!(matches!(
+      Backend::parse("mir"),
+      Err(EventLoopError::UnsupportedBackend(name)) if name == "mir"
+    ));
+  }
+}
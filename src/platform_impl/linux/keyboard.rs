@@ -9,15 +9,21 @@ use crate::{
 };
 use gtk::{
   gdk::{self, keys::constants::*, EventKey},
-  glib,
+  gio, glib,
+  prelude::*,
 };
 use std::{
+  cell::{Cell, RefCell},
   collections::HashSet,
   ffi::c_void,
   os::raw::{c_int, c_uint},
-  ptr, slice,
+  ptr,
+  rc::Rc,
+  slice,
   sync::Mutex,
+  time::Duration,
 };
+use x11_dl::xlib;
 
 pub type RawKey = gdk::keys::Key;
 
@@ -245,6 +251,193 @@ pub(crate) fn make_key_event(
   None
 }
 
+/// The key repeat settings of the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyRepeatSettings {
+  /// Whether held keys repeat at all.
+  pub enabled: bool,
+  /// How long a key has to be held before it starts repeating.
+  pub delay: Duration,
+  /// The time between two repeated key presses.
+  pub interval: Duration,
+}
+
+impl Default for KeyRepeatSettings {
+  fn default() -> Self {
+    // Same defaults as `org.gnome.desktop.peripherals.keyboard`.
+    Self {
+      enabled: true,
+      delay: Duration::from_millis(500),
+      interval: Duration::from_millis(30),
+    }
+  }
+}
+
+const KEYBOARD_SCHEMA: &str = "org.gnome.desktop.peripherals.keyboard";
+// `XkbUseCoreKbd` from `XKB.h`, it is not exposed by `x11_dl`.
+const XKB_USE_CORE_KBD: c_uint = 0x0100;
+
+/// Query the key repeat settings of the system.
+///
+/// On X11 the values are read from the XKB controls of the core keyboard. Wayland compositors
+/// only advertise them to the client which GDK does not expose, so we read the desktop settings
+/// they are configured from instead.
+fn key_repeat_settings(
+  display: &gdk::Display,
+  gsettings: Option<&gio::Settings>,
+) -> KeyRepeatSettings {
+  let settings = if display.backend().is_x11() {
+    xkb_key_repeat_settings(display).or_else(|| gsettings.map(gsettings_key_repeat_settings))
+  } else {
+    gsettings.map(gsettings_key_repeat_settings)
+  };
+  settings.unwrap_or_default()
+}
+
+fn keyboard_gsettings() -> Option<gio::Settings> {
+  // `gio::Settings::new` aborts if the schema isn't installed.
+  gio::SettingsSchemaSource::default()?.lookup(KEYBOARD_SCHEMA, true)?;
+  Some(gio::Settings::new(KEYBOARD_SCHEMA))
+}
+
+fn gsettings_key_repeat_settings(settings: &gio::Settings) -> KeyRepeatSettings {
+  KeyRepeatSettings {
+    enabled: settings.boolean("repeat"),
+    delay: Duration::from_millis(settings.uint("delay") as u64),
+    interval: Duration::from_millis(settings.uint("repeat-interval") as u64),
+  }
+}
+
+/// The key repeat settings of the system, queried once per event loop and refreshed when the
+/// desktop settings change.
+#[derive(Clone)]
+pub(crate) struct KeyRepeatSettingsCache {
+  display: gdk::Display,
+  gsettings: Option<gio::Settings>,
+  settings: Rc<Cell<KeyRepeatSettings>>,
+}
+
+impl KeyRepeatSettingsCache {
+  pub fn new(display: &gdk::Display) -> Self {
+    let gsettings = keyboard_gsettings();
+    let settings = Rc::new(Cell::new(key_repeat_settings(display, gsettings.as_ref())));
+    if let Some(gsettings) = &gsettings {
+      let display = display.clone();
+      let settings = settings.clone();
+      gsettings.connect_changed(None, move |gsettings, _| {
+        settings.set(key_repeat_settings(&display, Some(gsettings)));
+      });
+    }
+    Self {
+      display: display.clone(),
+      gsettings,
+      settings,
+    }
+  }
+
+  pub fn get(&self) -> KeyRepeatSettings {
+    self.settings.get()
+  }
+
+  /// Queries the settings again. XKB controls changed with e.g. `xset r rate` don't notify us, so
+  /// this is also done when a window gains focus.
+  pub fn refresh(&self) {
+    self
+      .settings
+      .set(key_repeat_settings(&self.display, self.gsettings.as_ref()));
+  }
+}
+
+fn xkb_key_repeat_settings(display: &gdk::Display) -> Option<KeyRepeatSettings> {
+  let xlib = xlib::Xlib::open().ok()?;
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+    if xdisplay.is_null() {
+      return None;
+    }
+
+    let (mut delay, mut interval) = (0, 0);
+    if (xlib.XkbGetAutoRepeatRate)(xdisplay as _, XKB_USE_CORE_KBD, &mut delay, &mut interval) == 0
+    {
+      return None;
+    }
+
+    let mut keyboard_state = std::mem::zeroed::<xlib::XKeyboardState>();
+    (xlib.XGetKeyboardControl)(xdisplay as _, &mut keyboard_state);
+
+    Some(KeyRepeatSettings {
+      enabled: keyboard_state.global_auto_repeat == xlib::AutoRepeatModeOn,
+      delay: Duration::from_millis(delay as u64),
+      interval: Duration::from_millis(interval as u64),
+    })
+  }
+}
+
+/// Keeps track of the held keys of a window to tell OS key repeats apart from the initial press,
+/// and of the timer generating synthetic repeats if they are enabled.
+#[derive(Default)]
+pub(crate) struct KeyRepeatState {
+  pressed: HashSet<u16>,
+  synthetic: Option<(u16, glib::SourceId)>,
+}
+
+impl KeyRepeatState {
+  /// Records a key press and returns `true` if the key was already held, i.e. this is a repeat.
+  pub fn press(&mut self, keycode: u16) -> bool {
+    !self.pressed.insert(keycode)
+  }
+
+  pub fn release(&mut self, keycode: u16) {
+    self.pressed.remove(&keycode);
+    if matches!(self.synthetic, Some((code, _)) if code == keycode) {
+      self.stop_synthetic();
+    }
+  }
+
+  /// Forgets all held keys, used when the window loses focus and won't receive their release.
+  pub fn clear(&mut self) {
+    self.pressed.clear();
+    self.stop_synthetic();
+  }
+
+  fn stop_synthetic(&mut self) {
+    if let Some((_, source)) = self.synthetic.take() {
+      source.remove();
+    }
+  }
+}
+
+/// Starts repeating `keycode` with the given settings by calling `repeat` until the key is
+/// released or another key is pressed, like the OS would.
+pub(crate) fn start_synthetic_repeat<F>(
+  state: &Rc<RefCell<KeyRepeatState>>,
+  keycode: u16,
+  settings: KeyRepeatSettings,
+  repeat: F,
+) where
+  F: Fn() + 'static,
+{
+  state.borrow_mut().stop_synthetic();
+  if !settings.enabled {
+    return;
+  }
+
+  // An interval of 0 would turn into a busy loop.
+  let interval = settings.interval.max(Duration::from_millis(1));
+  let state_ = state.clone();
+  let source = glib::timeout_add_local_once(settings.delay, move || {
+    repeat();
+    // The delay source is done at this point so it must not be removed anymore, swap it out
+    // before anything can stop the repeat.
+    let source = glib::timeout_add_local(interval, move || {
+      repeat();
+      glib::ControlFlow::Continue
+    });
+    state_.borrow_mut().synthetic = Some((keycode, source));
+  });
+  state.borrow_mut().synthetic = Some((keycode, source));
+}
+
 /// Map a hardware keycode to a keyval by performing a lookup in the keymap and finding the
 /// keyval with the lowest group and level
 fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
@@ -286,3 +479,23 @@ fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
   }
   None
 }
+
+#[cfg(test)]
+mod tests {
+  use super::KeyRepeatState;
+
+  #[test]
+  fn key_repeat_state_detects_repeats() {
+    let mut state = KeyRepeatState::default();
+    assert!(!state.press(38));
+    assert!(state.press(38));
+    assert!(!state.press(39));
+
+    state.release(38);
+    assert!(!state.press(38));
+
+    state.clear();
+    assert!(!state.press(38));
+    assert!(!state.press(39));
+  }
+}
@@ -8,6 +8,9 @@ mod icon;
 mod keyboard;
 mod keycode;
 mod monitor;
+mod scale;
+mod sources;
+mod user_events;
 mod util;
 mod window;
 
@@ -17,9 +20,13 @@ pub mod x11;
 
 pub use self::keycode::{keycode_from_scancode, keycode_to_scancode};
 pub(crate) use event_loop::PlatformSpecificEventLoopAttributes;
-pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use event_loop::{Backend, EventLoop, EventLoopProxy, EventLoopWindowTarget, RunOnMain};
 pub use icon::PlatformIcon;
-pub use monitor::{MonitorHandle, VideoMode};
+pub use keyboard::KeyRepeatSettings;
+pub use monitor::{MonitorHandle, MonitorRotation, SubpixelLayout, VideoMode};
+pub use scale::ScaleFactorOverride;
+pub use sources::FdWatch;
+pub use user_events::{SendEvent, UserEventPriority};
 pub use window::{Window, WindowId};
 
 use crate::{event::DeviceId as RootDeviceId, keyboard::Key};
@@ -53,6 +60,8 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub rgba_visual: bool,
   pub cursor_moved: bool,
   pub default_vbox: bool,
+  pub motion_compression: bool,
+  pub motion_history: bool,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -66,6 +75,8 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       rgba_visual: false,
       cursor_moved: true,
       default_vbox: true,
+      motion_compression: true,
+      motion_history: false,
     }
   }
 }
@@ -73,12 +84,22 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
 unsafe impl Send for PlatformSpecificWindowBuilderAttributes {}
 unsafe impl Sync for PlatformSpecificWindowBuilderAttributes {}
 
-#[derive(Debug, Clone)]
-pub struct OsError;
+/// An error reported by GTK or GLib, with its message if there is one.
+#[derive(Debug, Clone, Default)]
+pub struct OsError(Option<String>);
+
+impl OsError {
+  pub(crate) fn new(message: impl Into<String>) -> Self {
+    Self(Some(message.into()))
+  }
+}
 
 impl std::fmt::Display for OsError {
-  fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-    Ok(())
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+    match &self.0 {
+      Some(message) => f.pad(message),
+      None => Ok(()),
+    }
   }
 }
 
@@ -93,3 +114,17 @@ impl DeviceId {
 
 // FIXME: currently we use a dummy device id, find if we can get device id from gtk
 pub(crate) const DEVICE_ID: RootDeviceId = RootDeviceId(DeviceId(0));
+
+#[cfg(test)]
+mod tests {
+  use super::OsError;
+
+  #[test]
+  fn os_error_keeps_message() {
+    assert_eq!(
+      OsError::new("Failed to register: Timeout was reached").to_string(),
+      "Failed to register: Timeout was reached"
+    );
+    assert_eq!(OsError::default().to_string(), "");
+  }
+}
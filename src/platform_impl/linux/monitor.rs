@@ -2,11 +2,30 @@
 // Copyright 2021-2023 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use gtk::gdk::{self, prelude::MonitorExt, Display};
+use std::{
+  cell::RefCell,
+  ffi::{CStr, CString},
+  os::raw::{c_char, c_int, c_long},
+  ptr,
+  rc::Rc,
+  slice,
+};
+
+use gtk::{
+  gdk::{
+    self,
+    prelude::{DisplayExtManual, MonitorExt},
+    Display,
+  },
+  glib::{self, prelude::ObjectExt, ObjectType},
+  prelude::{GtkApplicationExt, GtkWindowExt, WidgetExt},
+};
 
+use super::{scale, user_events::EventSender, x11::ffi};
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  event::Event,
+  monitor::{MonitorHandle as RootMonitorHandle, Rect, VideoMode as RootVideoMode},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -32,7 +51,7 @@ impl MonitorHandle {
       width: rect.width() as u32,
       height: rect.height() as u32,
     }
-    .to_physical(self.scale_factor())
+    .to_physical(self.monitor.scale_factor() as f64)
   }
 
   #[inline]
@@ -42,18 +61,430 @@ impl MonitorHandle {
       x: rect.x(),
       y: rect.y(),
     }
-    .to_physical(self.scale_factor())
+    .to_physical(self.monitor.scale_factor() as f64)
+  }
+
+  #[inline]
+  pub fn work_area(&self) -> Rect {
+    let rect = self.monitor.workarea();
+    let scale_factor = self.monitor.scale_factor() as f64;
+    Rect {
+      position: LogicalPosition::new(rect.x(), rect.y()).to_physical(scale_factor),
+      size: LogicalSize::new(rect.width() as u32, rect.height() as u32).to_physical(scale_factor),
+    }
   }
 
   #[inline]
   pub fn scale_factor(&self) -> f64 {
-    self.monitor.scale_factor() as f64
+    scale::scale_factor(self)
+  }
+
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    match self.monitor.refresh_rate() {
+      0 => None,
+      rate => Some(rate as u32),
+    }
+  }
+
+  #[inline]
+  pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+    match (self.monitor.width_mm(), self.monitor.height_mm()) {
+      (width, height) if width > 0 && height > 0 => Some((width as u32, height as u32)),
+      _ => None,
+    }
+  }
+
+  pub fn icc_profile(&self) -> Option<Vec<u8>> {
+    let display = self.monitor.display()?;
+    if !display.backend().is_x11() {
+      return None;
+    }
+    // Following the ICC Profiles in X Specification, `_ICC_PROFILE` is for the first Xinerama
+    // screen and `_ICC_PROFILE_n` for the others.
+    let name = match icc_profile_index(&self.monitor)? {
+      0 => "_ICC_PROFILE".to_string(),
+      index => format!("_ICC_PROFILE_{index}"),
+    };
+
+    let xlib = ffi::Xlib::open().ok()?;
+    unsafe {
+      let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+      if xdisplay.is_null() {
+        return None;
+      }
+      let xdisplay = xdisplay as *mut ffi::Display;
+      let name = CString::new(name).ok()?;
+      let atom = (xlib.XInternAtom)(xdisplay, name.as_ptr(), ffi::True);
+      if atom == 0 {
+        return None;
+      }
+
+      let mut actual_type = 0;
+      let mut actual_format = 0;
+      let mut nitems = 0;
+      let mut bytes_after = 0;
+      let mut data = ptr::null_mut();
+      let status = (xlib.XGetWindowProperty)(
+        xdisplay,
+        (xlib.XDefaultRootWindow)(xdisplay),
+        atom,
+        0,
+        c_long::MAX,
+        ffi::False,
+        ffi::AnyPropertyType as _,
+        &mut actual_type,
+        &mut actual_format,
+        &mut nitems,
+        &mut bytes_after,
+        &mut data,
+      );
+      if status != ffi::Success as c_int || data.is_null() {
+        return None;
+      }
+      let profile = (actual_format == 8 && nitems > 0)
+        .then(|| slice::from_raw_parts(data, nitems as usize).to_vec());
+      (xlib.XFree)(data as *mut _);
+      profile
+    }
   }
 
   #[inline]
   pub fn video_modes(&self) -> Box<dyn Iterator<Item = RootVideoMode>> {
     Box::new(Vec::new().into_iter())
   }
+
+  #[inline]
+  pub fn manufacturer(&self) -> Option<String> {
+    self
+      .monitor
+      .manufacturer()
+      .map(|s| s.as_str().to_string())
+      .or_else(|| {
+        // GDK only knows the manufacturer on Wayland.
+        randr_output(&self.monitor)?
+          .edid
+          .as_deref()
+          .and_then(edid_manufacturer)
+      })
+  }
+
+  #[inline]
+  pub fn connector(&self) -> Option<String> {
+    randr_output(&self.monitor).map(|output| output.connector)
+  }
+
+  #[inline]
+  pub fn subpixel_layout(&self) -> SubpixelLayout {
+    match self.monitor.subpixel_layout() {
+      gdk::SubpixelLayout::None => SubpixelLayout::None,
+      gdk::SubpixelLayout::HorizontalRgb => SubpixelLayout::HorizontalRgb,
+      gdk::SubpixelLayout::HorizontalBgr => SubpixelLayout::HorizontalBgr,
+      gdk::SubpixelLayout::VerticalRgb => SubpixelLayout::VerticalRgb,
+      gdk::SubpixelLayout::VerticalBgr => SubpixelLayout::VerticalBgr,
+      _ => SubpixelLayout::Unknown,
+    }
+  }
+
+  #[inline]
+  pub fn rotation(&self) -> Option<MonitorRotation> {
+    randr_rotation(&self.monitor)
+  }
+
+  pub fn id(&self) -> String {
+    if let Some(output) = randr_output(&self.monitor) {
+      return match output.edid.as_deref().and_then(edid_identifier) {
+        Some((id, true)) => id,
+        // Identical monitors without a serial number are told apart by their connector.
+        Some((id, false)) => format!("{id}@{}", output.connector),
+        None => output.connector,
+      };
+    }
+
+    let model = |monitor: &gdk::Monitor| {
+      (
+        monitor.manufacturer().map(|s| s.to_string()),
+        monitor.model().map(|s| s.to_string()),
+      )
+    };
+    let (manufacturer, name) = model(&self.monitor);
+    // Monitors GDK knows nothing about are told apart by their position like identical ones.
+    let id = match (&manufacturer, &name) {
+      (Some(manufacturer), Some(name)) => format!("{manufacturer} {name}"),
+      (Some(id), None) | (None, Some(id)) => id.clone(),
+      (None, None) => "unknown".to_string(),
+    };
+
+    // GDK doesn't expose the connector on Wayland, so identical monitors are told apart by their
+    // order from left to right, then top to bottom.
+    let Some(display) = self.monitor.display() else {
+      return id;
+    };
+    let mut twins: Vec<_> = (0..display.n_monitors())
+      .filter_map(|i| display.monitor(i))
+      .filter(|monitor| model(monitor) == (manufacturer.clone(), name.clone()))
+      .collect();
+    if twins.len() < 2 {
+      return id;
+    }
+    twins.sort_by_key(|monitor| {
+      let rect = monitor.geometry();
+      (rect.x(), rect.y())
+    });
+    match twins.iter().position(|monitor| *monitor == self.monitor) {
+      Some(i) => format!("{id}#{}", i + 1),
+      None => id,
+    }
+  }
+}
+
+/// The order of the color subpixels of a monitor.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubpixelLayout {
+  Unknown,
+  /// The monitor doesn't have subpixels, or they shouldn't be used for rendering.
+  None,
+  HorizontalRgb,
+  HorizontalBgr,
+  VerticalRgb,
+  VerticalBgr,
+}
+
+/// How much the picture of a monitor is rotated, counterclockwise.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonitorRotation {
+  Normal,
+  Rotate90,
+  Rotate180,
+  Rotate270,
+}
+
+/// The Xinerama screen number of `monitor`, which `_ICC_PROFILE_n` properties are indexed by.
+///
+/// RandR exposes its active monitors as Xinerama screens, in the order `XRRGetMonitors` lists
+/// them. That isn't GDK's order once monitors have been connected and disconnected, so GDK's is
+/// only used without RandR 1.5.
+fn icc_profile_index(monitor: &gdk::Monitor) -> Option<usize> {
+  let display = monitor.display()?;
+  xinerama_screen(&display, monitor).or_else(|| {
+    (0..display.n_monitors()).position(|i| display.monitor(i).as_ref() == Some(monitor))
+  })
+}
+
+fn xinerama_screen(display: &Display, monitor: &gdk::Monitor) -> Option<usize> {
+  if !display.backend().is_x11() {
+    return None;
+  }
+  let xlib = ffi::Xlib::open().ok()?;
+  let xrandr = ffi::Xrandr::open().ok()?;
+
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+    let output = gdk_x11_sys::gdk_x11_monitor_get_output(monitor.as_ptr() as *mut _);
+    if xdisplay.is_null() || output == 0 {
+      return None;
+    }
+    let xdisplay = xdisplay as *mut ffi::Display;
+
+    let mut count = 0;
+    let monitors = (xrandr.XRRGetMonitors)(
+      xdisplay,
+      (xlib.XDefaultRootWindow)(xdisplay),
+      ffi::True,
+      &mut count,
+    );
+    if monitors.is_null() {
+      return None;
+    }
+    let index = slice::from_raw_parts(monitors, count.max(0) as usize)
+      .iter()
+      .position(|info| {
+        !info.outputs.is_null()
+          && slice::from_raw_parts(info.outputs, info.noutput.max(0) as usize).contains(&output)
+      });
+    (xrandr.XRRFreeMonitors)(monitors);
+    index
+  }
+}
+
+/// What XRandR knows about the output a monitor is connected to. It doesn't change while the
+/// monitor exists, GDK creates a new monitor when another one is plugged into the output.
+#[derive(Clone)]
+struct RandrOutput {
+  connector: String,
+  edid: Option<Vec<u8>>,
+}
+
+/// Looks the output of `monitor` up once and keeps it on the monitor.
+fn randr_output(monitor: &gdk::Monitor) -> Option<RandrOutput> {
+  const KEY: &str = "tao-randr-output";
+
+  if let Some(output) = unsafe { monitor.data::<Option<RandrOutput>>(KEY) } {
+    return unsafe { output.as_ref() }.clone();
+  }
+  let output = with_output_info(monitor, |xlib, xrandr, xdisplay, _, output, info| unsafe {
+    RandrOutput {
+      connector: String::from_utf8_lossy(slice::from_raw_parts(
+        (*info).name as *const u8,
+        (*info).nameLen as usize,
+      ))
+      .into_owned(),
+      edid: output_edid(xlib, xrandr, xdisplay, output),
+    }
+  });
+  unsafe { monitor.set_data(KEY, output.clone()) };
+  output
+}
+
+/// Queries the rotation of the CRTC driving `monitor`, which changes when the monitor is
+/// reconfigured.
+fn randr_rotation(monitor: &gdk::Monitor) -> Option<MonitorRotation> {
+  with_output_info(monitor, |_, xrandr, xdisplay, resources, _, info| unsafe {
+    if (*info).crtc == 0 {
+      return MonitorRotation::Normal;
+    }
+    let crtc = (xrandr.XRRGetCrtcInfo)(xdisplay, resources, (*info).crtc);
+    if crtc.is_null() {
+      return MonitorRotation::Normal;
+    }
+    let rotation = match (*crtc).rotation as c_int {
+      r if r & ffi::RR_Rotate_90 != 0 => MonitorRotation::Rotate90,
+      r if r & ffi::RR_Rotate_180 != 0 => MonitorRotation::Rotate180,
+      r if r & ffi::RR_Rotate_270 != 0 => MonitorRotation::Rotate270,
+      _ => MonitorRotation::Normal,
+    };
+    (xrandr.XRRFreeCrtcInfo)(crtc);
+    rotation
+  })
+}
+
+/// Calls `f` with the XRandR screen resources and output info of `monitor`, on X11.
+fn with_output_info<R>(
+  monitor: &gdk::Monitor,
+  f: impl FnOnce(
+    &ffi::Xlib,
+    &ffi::Xrandr_2_2_0,
+    *mut ffi::Display,
+    *mut ffi::XRRScreenResources,
+    ffi::RROutput,
+    *mut ffi::XRROutputInfo,
+  ) -> R,
+) -> Option<R> {
+  let display = monitor.display()?;
+  if !display.backend().is_x11() {
+    return None;
+  }
+  let xlib = ffi::Xlib::open().ok()?;
+  let xrandr = ffi::Xrandr_2_2_0::open().ok()?;
+
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+    let output = gdk_x11_sys::gdk_x11_monitor_get_output(monitor.as_ptr() as *mut _);
+    if xdisplay.is_null() || output == 0 {
+      return None;
+    }
+    let xdisplay = xdisplay as *mut ffi::Display;
+
+    let resources =
+      (xrandr.XRRGetScreenResourcesCurrent)(xdisplay, (xlib.XDefaultRootWindow)(xdisplay));
+    if resources.is_null() {
+      return None;
+    }
+    let info = (xrandr.XRRGetOutputInfo)(xdisplay, resources, output);
+    let result = (!info.is_null()).then(|| f(&xlib, &xrandr, xdisplay, resources, output, info));
+
+    if !info.is_null() {
+      (xrandr.XRRFreeOutputInfo)(info);
+    }
+    (xrandr.XRRFreeScreenResources)(resources);
+    result
+  }
+}
+
+unsafe fn output_edid(
+  xlib: &ffi::Xlib,
+  xrandr: &ffi::Xrandr_2_2_0,
+  xdisplay: *mut ffi::Display,
+  output: ffi::RROutput,
+) -> Option<Vec<u8>> {
+  let atom = (xlib.XInternAtom)(xdisplay, b"EDID\0".as_ptr() as *const c_char, ffi::True);
+  if atom == 0 {
+    return None;
+  }
+
+  let mut actual_type = 0;
+  let mut actual_format = 0;
+  let mut nitems = 0;
+  let mut bytes_after = 0;
+  let mut data = ptr::null_mut();
+  let status = (xrandr.XRRGetOutputProperty)(
+    xdisplay,
+    output,
+    atom,
+    0,
+    // In 32-bit units, enough for the base block and a few extensions.
+    256,
+    ffi::False,
+    ffi::False,
+    ffi::AnyPropertyType as _,
+    &mut actual_type,
+    &mut actual_format,
+    &mut nitems,
+    &mut bytes_after,
+    &mut data,
+  );
+  if status != ffi::Success as c_int || data.is_null() {
+    return None;
+  }
+
+  let edid = (actual_format == 8 && nitems >= 128)
+    .then(|| slice::from_raw_parts(data, nitems as usize).to_vec());
+  (xlib.XFree)(data as *mut _);
+  edid
+}
+
+/// Returns the three-letter PNP ID of the manufacturer in an EDID.
+fn edid_manufacturer(edid: &[u8]) -> Option<String> {
+  if edid.len() < 128 || edid[..8] != [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00] {
+    return None;
+  }
+
+  let id = u16::from_be_bytes([edid[8], edid[9]]);
+  Some(
+    [10, 5, 0]
+      .iter()
+      .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1f) as u8) as char)
+      .collect(),
+  )
+}
+
+/// Builds an identifier from the manufacturer, product code and serial number of an EDID.
+/// Also returns whether the EDID has a serial number.
+fn edid_identifier(edid: &[u8]) -> Option<(String, bool)> {
+  let manufacturer = edid_manufacturer(edid)?;
+  let product = u16::from_le_bytes([edid[10], edid[11]]);
+  let serial = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]);
+
+  // Many monitors leave the serial number at 0 and put it in a display descriptor instead.
+  let serial_string = edid[54..126]
+    .chunks(18)
+    .find(|descriptor| descriptor[..4] == [0, 0, 0, 0xff])
+    .map(|descriptor| {
+      String::from_utf8_lossy(&descriptor[5..])
+        .trim_end_matches(['\n', ' ', '\0'])
+        .to_string()
+    });
+
+  Some(match serial_string {
+    Some(serial_string) if !serial_string.is_empty() => (
+      format!("{manufacturer}-{product:04X}-{serial_string}"),
+      true,
+    ),
+    _ if serial != 0 => (format!("{manufacturer}-{product:04X}-{serial:08X}"), true),
+    _ => (format!("{manufacturer}-{product:04X}"), false),
+  })
 }
 
 unsafe impl Send for MonitorHandle {}
@@ -84,13 +515,323 @@ impl VideoMode {
   }
 }
 
+/// Starts emitting `Event::MonitorsChanged` when monitors are connected, disconnected or
+/// reconfigured, until [`MonitorWatch::stop`] is called.
+pub fn watch_monitors<T: 'static>(
+  display: &Display,
+  app: &gtk::Application,
+  event_tx: EventSender<T>,
+  relocate_orphaned_windows: bool,
+) -> MonitorWatch<T> {
+  let watcher = Rc::new(MonitorWatcher {
+    display: display.clone(),
+    app: app.clone(),
+    event_tx,
+    // Wayland compositors place windows themselves and don't report their position.
+    relocate_orphaned_windows: relocate_orphaned_windows && display.backend().is_x11(),
+    pending: Default::default(),
+    monitor_handlers: Default::default(),
+    icc_filter: Default::default(),
+  });
+
+  for monitor in (0..display.n_monitors()).filter_map(|i| display.monitor(i)) {
+    watcher.watch(&monitor);
+  }
+  watcher.watch_icc_profiles();
+
+  let watcher_ = watcher.clone();
+  let added = display.connect_monitor_added(move |_, monitor| {
+    watcher_.watch(monitor);
+    watcher_.record(|pending| pending.added.push(monitor.clone()));
+  });
+  let watcher_ = watcher.clone();
+  let removed = display.connect_monitor_removed(move |_, monitor| {
+    watcher_.unwatch(monitor);
+    watcher_.record(
+      |pending| match pending.added.iter().position(|added| added == monitor) {
+        Some(i) => {
+          pending.added.remove(i);
+        }
+        None => pending.removed.push(monitor.clone()),
+      },
+    );
+  });
+
+  MonitorWatch {
+    watcher,
+    added,
+    removed,
+  }
+}
+
+/// The handlers installed by [`watch_monitors`].
+pub struct MonitorWatch<T: 'static> {
+  watcher: Rc<MonitorWatcher<T>>,
+  added: glib::SignalHandlerId,
+  removed: glib::SignalHandlerId,
+}
+
+impl<T: 'static> MonitorWatch<T> {
+  /// Disconnects the handlers from the display and its monitors and removes the `_ICC_PROFILE`
+  /// filter, which would otherwise outlive the event loop with the display.
+  pub fn stop(self) {
+    let watcher = self.watcher;
+    watcher.display.disconnect(self.added);
+    watcher.display.disconnect(self.removed);
+    for (monitor, handler) in watcher.monitor_handlers.take() {
+      monitor.disconnect(handler);
+    }
+    if let Some((root, callback)) = watcher.icc_filter.take() {
+      unsafe {
+        gdk::ffi::gdk_window_remove_filter(root.as_ptr(), Some(icc_profile_filter), callback as _);
+        drop(Box::from_raw(callback));
+      }
+    }
+  }
+}
+
+/// Monitor changes waiting for the main loop to be idle, to be reported in a single event.
+#[derive(Default)]
+struct PendingChanges {
+  added: Vec<gdk::Monitor>,
+  removed: Vec<gdk::Monitor>,
+  changed: Vec<gdk::Monitor>,
+  scheduled: bool,
+}
+
+struct MonitorWatcher<T: 'static> {
+  display: Display,
+  app: gtk::Application,
+  event_tx: EventSender<T>,
+  relocate_orphaned_windows: bool,
+  pending: RefCell<PendingChanges>,
+  /// The `notify` handlers of the monitors.
+  monitor_handlers: RefCell<Vec<(gdk::Monitor, glib::SignalHandlerId)>>,
+  /// The root window the `_ICC_PROFILE` filter is installed on, and the data of the filter.
+  icc_filter: RefCell<Option<(gdk::Window, *mut IccProfileCallback)>>,
+}
+
+impl<T: 'static> MonitorWatcher<T> {
+  fn watch(self: &Rc<Self>, monitor: &gdk::Monitor) {
+    let watcher = Rc::downgrade(self);
+    let handler = monitor.connect_notify_local(None, move |monitor, pspec| {
+      if !matches!(pspec.name(), "geometry" | "workarea" | "scale-factor") {
+        return;
+      }
+      if let Some(watcher) = watcher.upgrade() {
+        watcher.record(|pending| {
+          if !pending.changed.contains(monitor) {
+            pending.changed.push(monitor.clone());
+          }
+        });
+      }
+    });
+    self
+      .monitor_handlers
+      .borrow_mut()
+      .push((monitor.clone(), handler));
+  }
+
+  fn unwatch(&self, monitor: &gdk::Monitor) {
+    let mut monitor_handlers = self.monitor_handlers.borrow_mut();
+    if let Some(i) = monitor_handlers.iter().position(|(m, _)| m == monitor) {
+      let (monitor, handler) = monitor_handlers.remove(i);
+      monitor.disconnect(handler);
+    }
+  }
+
+  /// Reports the monitors whose `_ICC_PROFILE` root window property changes, on X11.
+  fn watch_icc_profiles(self: &Rc<Self>) {
+    if !self.display.backend().is_x11() {
+      return;
+    }
+    let Some(root) = self.display.default_screen().root_window() else {
+      return;
+    };
+    root.set_events(root.events() | gdk::EventMask::PROPERTY_CHANGE_MASK);
+
+    let watcher = Rc::downgrade(self);
+    let callback: IccProfileCallback = Box::new(move |index| {
+      let Some(watcher) = watcher.upgrade() else {
+        return;
+      };
+      let display = &watcher.display;
+      if let Some(monitor) = (0..display.n_monitors())
+        .filter_map(|i| display.monitor(i))
+        .find(|monitor| icc_profile_index(monitor) == Some(index))
+      {
+        watcher.record(|pending| {
+          if !pending.changed.contains(&monitor) {
+            pending.changed.push(monitor.clone());
+          }
+        });
+      }
+    });
+    // Removed along with its data by `MonitorWatch::stop`.
+    let callback = Box::into_raw(Box::new(callback));
+    unsafe {
+      gdk::ffi::gdk_window_add_filter(root.as_ptr(), Some(icc_profile_filter), callback as _);
+    }
+    *self.icc_filter.borrow_mut() = Some((root, callback));
+  }
+
+  fn record(self: &Rc<Self>, change: impl FnOnce(&mut PendingChanges)) {
+    let mut pending = self.pending.borrow_mut();
+    change(&mut pending);
+    if !pending.scheduled {
+      pending.scheduled = true;
+      let watcher = Rc::downgrade(self);
+      glib::idle_add_local_once(move || {
+        if let Some(watcher) = watcher.upgrade() {
+          watcher.flush();
+        }
+      });
+    }
+  }
+
+  fn flush(&self) {
+    let PendingChanges {
+      added,
+      removed,
+      mut changed,
+      ..
+    } = std::mem::take(&mut *self.pending.borrow_mut());
+    changed.retain(|monitor| !added.contains(monitor) && !removed.contains(monitor));
+
+    if self.relocate_orphaned_windows {
+      relocate_orphaned_windows(&self.display, &self.app);
+    }
+
+    let handles = |monitors: Vec<gdk::Monitor>| {
+      monitors
+        .into_iter()
+        .map(|monitor| RootMonitorHandle {
+          inner: MonitorHandle { monitor },
+        })
+        .collect()
+    };
+    if let Err(e) = self.event_tx.send(Event::MonitorsChanged {
+      added: handles(added),
+      removed: handles(removed),
+      changed: handles(changed),
+    }) {
+      log::warn!(
+        "Failed to send monitors changed event to event channel: {}",
+        e
+      );
+    }
+  }
+}
+
+/// Called with the Xinerama screen number whose ICC profile changed.
+type IccProfileCallback = Box<dyn Fn(usize)>;
+
+unsafe extern "C" fn icc_profile_filter(
+  xevent: *mut gdk::ffi::GdkXEvent,
+  _event: *mut gdk::ffi::GdkEvent,
+  data: glib::ffi::gpointer,
+) -> gdk::ffi::GdkFilterReturn {
+  let xevent = &*(xevent as *const ffi::XEvent);
+  if xevent.get_type() == ffi::PropertyNotify {
+    let name = gdk_x11_sys::gdk_x11_get_xatom_name(xevent.property.atom);
+    if !name.is_null() {
+      let index = match CStr::from_ptr(name).to_bytes() {
+        b"_ICC_PROFILE" => Some(0),
+        name => name
+          .strip_prefix(b"_ICC_PROFILE_")
+          .and_then(|index| std::str::from_utf8(index).ok()?.parse().ok()),
+      };
+      if let Some(index) = index {
+        (*(data as *const IccProfileCallback))(index);
+      }
+    }
+  }
+  gdk::ffi::GDK_FILTER_CONTINUE
+}
+
+/// Moves the visible windows that don't overlap any monitor anymore to the primary monitor.
+fn relocate_orphaned_windows(display: &Display, app: &gtk::Application) {
+  let monitors: Vec<_> = (0..display.n_monitors())
+    .filter_map(|i| display.monitor(i))
+    .collect();
+  let Some(target) = display
+    .primary_monitor()
+    .or_else(|| monitors.first().cloned())
+  else {
+    return;
+  };
+
+  for window in app.windows() {
+    if !window.is_visible() {
+      continue;
+    }
+    let (x, y) = window.position();
+    let (width, height) = window.size();
+    let orphaned = !monitors.iter().any(|monitor| {
+      let rect = monitor.geometry();
+      x < rect.x() + rect.width()
+        && x + width > rect.x()
+        && y < rect.y() + rect.height()
+        && y + height > rect.y()
+    });
+    if orphaned {
+      let area = target.workarea();
+      window.move_(
+        area.x() + (area.width() - width).max(0) / 2,
+        area.y() + (area.height() - height).max(0) / 2,
+      );
+    }
+  }
+}
+
 pub fn from_point(display: &Display, x: f64, y: f64) -> Option<MonitorHandle> {
-  if let Some(monitor) = display.monitor_at_point(x as i32, y as i32) {
-    (0..display.n_monitors())
-      .map(|i| (i, display.monitor(i).unwrap()))
-      .find(|cur| cur.1.geometry() == monitor.geometry())
-      .map(|x| MonitorHandle::new(display, x.0))
-  } else {
-    None
+  display
+    .monitor_at_point(x as i32, y as i32)
+    .map(|monitor| MonitorHandle { monitor })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{edid_identifier, edid_manufacturer};
+
+  /// A base EDID block of a `DEL` monitor with product code `A0B1` and the given serial number.
+  fn edid(serial: u32) -> Vec<u8> {
+    let mut edid = vec![0; 128];
+    edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+    edid[8..10].copy_from_slice(&[0x10, 0xac]);
+    edid[10..12].copy_from_slice(&0xa0b1u16.to_le_bytes());
+    edid[12..16].copy_from_slice(&serial.to_le_bytes());
+    edid
+  }
+
+  #[test]
+  fn edid_manufacturer_decodes_pnp_id() {
+    assert_eq!(edid_manufacturer(&edid(0)).as_deref(), Some("DEL"));
+    assert_eq!(edid_manufacturer(&edid(0)[..127]), None);
+
+    let mut bad_header = edid(0);
+    bad_header[0] = 0xff;
+    assert_eq!(edid_manufacturer(&bad_header), None);
+  }
+
+  #[test]
+  fn edid_identifier_prefers_serial_descriptor() {
+    assert_eq!(
+      edid_identifier(&edid(0)),
+      Some(("DEL-A0B1".to_string(), false))
+    );
+    assert_eq!(
+      edid_identifier(&edid(0x1234)),
+      Some(("DEL-A0B1-00001234".to_string(), true))
+    );
+
+    // Serial number descriptor in the second slot, terminated by a newline and padded.
+    let mut edid = edid(0x1234);
+    edid[72..77].copy_from_slice(&[0, 0, 0, 0xff, 0]);
+    edid[77..90].copy_from_slice(b"CN0ABC123\n   ");
+    assert_eq!(
+      edid_identifier(&edid),
+      Some(("DEL-A0B1-CN0ABC123".to_string(), true))
+    );
   }
 }
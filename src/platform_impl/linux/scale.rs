@@ -0,0 +1,313 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  collections::HashMap,
+  os::raw::{c_int, c_long},
+  ptr, slice,
+  sync::RwLock,
+};
+
+use gtk::{
+  gdk::{
+    self,
+    prelude::{DisplayExtManual, MonitorExt},
+  },
+  glib::{self, IsA, ObjectExt, ObjectType},
+  prelude::WidgetExt,
+};
+
+use super::{monitor::MonitorHandle, x11::ffi};
+use crate::dpi::{validate_scale_factor, LogicalPosition, LogicalSize, Pixel, Position, Size};
+
+/// A scale factor forced in place of the one the system reports.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScaleFactorOverride {
+  /// Use this scale factor on every monitor.
+  Global(f64),
+  /// Use a scale factor per monitor, keyed by connector name (e.g. `DP-1`) or by the string form
+  /// of the monitor's [`MonitorId`](crate::monitor::MonitorId). Monitors that aren't listed keep
+  /// the scale factor reported by the system.
+  PerMonitor(HashMap<String, f64>),
+}
+
+static OVERRIDE: RwLock<Option<ScaleFactorOverride>> = RwLock::new(None);
+
+impl ScaleFactorOverride {
+  /// Environment variable overriding the one set with
+  /// `EventLoopBuilderExtUnix::with_scale_factor_override`.
+  const ENV_VAR: &str = "TAO_SCALE_FACTOR";
+
+  fn from_env() -> Option<Self> {
+    let value = std::env::var(Self::ENV_VAR).ok()?;
+    let scale_factor_override = Self::parse(&value);
+    if scale_factor_override.is_none() {
+      log::warn!("Ignoring invalid {} value: {:?}", Self::ENV_VAR, value);
+    }
+    scale_factor_override
+  }
+
+  /// Parses either a single scale factor, e.g. `1.5`, or a `;` separated list of
+  /// `monitor=scale` pairs, e.g. `DP-1=2;HDMI-1=1.25`.
+  fn parse(value: &str) -> Option<Self> {
+    let scale = |scale: &str| {
+      scale
+        .trim()
+        .parse()
+        .ok()
+        .filter(|scale| validate_scale_factor(*scale))
+    };
+
+    if !value.contains('=') {
+      return scale(value).map(ScaleFactorOverride::Global);
+    }
+    value
+      .split(';')
+      .filter(|pair| !pair.trim().is_empty())
+      .map(|pair| {
+        let (monitor, value) = pair.split_once('=')?;
+        Some((monitor.trim().to_string(), scale(value)?))
+      })
+      .collect::<Option<HashMap<_, _>>>()
+      .map(ScaleFactorOverride::PerMonitor)
+  }
+
+  fn get(&self, monitor: &MonitorHandle) -> Option<f64> {
+    match self {
+      ScaleFactorOverride::Global(scale_factor) => Some(*scale_factor),
+      ScaleFactorOverride::PerMonitor(scale_factors) => monitor
+        .connector()
+        .and_then(|connector| scale_factors.get(&connector))
+        .or_else(|| scale_factors.get(&monitor.id()))
+        .copied(),
+    }
+  }
+}
+
+/// Installs the scale factor override, the environment variable takes precedence over the one set
+/// on the event loop builder.
+pub(crate) fn set_override(scale_factor_override: Option<ScaleFactorOverride>) {
+  let scale_factor_override = ScaleFactorOverride::from_env().or(scale_factor_override);
+  *OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = scale_factor_override;
+}
+
+/// The scale factor reported for `monitor`.
+///
+/// This is the override if one applies, then on X11 the fractional scale derived from `Xft.dpi`,
+/// or from the monitor's physical size if `Xft.dpi` isn't set, and finally GDK's integer scale.
+/// The fractional scale never goes below GDK's, as GDK already renders at that scale.
+pub(crate) fn scale_factor(monitor: &MonitorHandle) -> f64 {
+  let scale_factor_override = OVERRIDE.read().unwrap_or_else(|e| e.into_inner());
+  if let Some(scale_factor) = scale_factor_override
+    .as_ref()
+    .and_then(|scale_factor_override| scale_factor_override.get(monitor))
+  {
+    return scale_factor;
+  }
+
+  let gdk_scale = monitor.monitor.scale_factor() as f64;
+  x11_scale_factor(&monitor.monitor)
+    .map(|scale_factor| scale_factor.max(gdk_scale))
+    .unwrap_or(gdk_scale)
+}
+
+/// The scale factor of the monitor `widget` is on, or of the primary monitor while it's hidden.
+pub(crate) fn widget_scale_factor(widget: &impl IsA<gtk::Widget>) -> f64 {
+  let display = widget.display();
+  let monitor = widget
+    .window()
+    .and_then(|window| display.monitor_at_window(&window))
+    .or_else(|| display.primary_monitor())
+    .or_else(|| display.monitor(0));
+
+  match monitor {
+    Some(monitor) => scale_factor(&MonitorHandle { monitor }),
+    None => widget.scale_factor() as f64,
+  }
+}
+
+/// Converts a size in `scale_factor` logical or physical pixels to GDK's coordinates, which are
+/// scaled by GDK's integer `gdk_scale`.
+pub(crate) fn size_to_gdk<T: Pixel>(
+  size: Size,
+  scale_factor: f64,
+  gdk_scale: i32,
+) -> LogicalSize<T> {
+  size
+    .to_physical::<f64>(scale_factor)
+    .to_logical(gdk_scale as f64)
+}
+
+/// Converts a position in `scale_factor` logical or physical pixels to GDK's coordinates, which
+/// are scaled by GDK's integer `gdk_scale`.
+pub(crate) fn position_to_gdk<T: Pixel>(
+  position: Position,
+  scale_factor: f64,
+  gdk_scale: i32,
+) -> LogicalPosition<T> {
+  position
+    .to_physical::<f64>(scale_factor)
+    .to_logical(gdk_scale as f64)
+}
+
+fn x11_scale_factor(monitor: &gdk::Monitor) -> Option<f64> {
+  let display = monitor.display()?;
+  if !display.backend().is_x11() {
+    return None;
+  }
+  xft_dpi(&display)
+    .map(|dpi| dpi / 96.0)
+    .or_else(|| physical_scale_factor(monitor))
+    .filter(|scale_factor| validate_scale_factor(*scale_factor))
+}
+
+/// Key of the `Xft.dpi` cached on the display.
+const XFT_DPI_KEY: &str = "tao-xft-dpi";
+
+/// `Xft.dpi` from the X resource database, read once until it changes.
+fn xft_dpi(display: &gdk::Display) -> Option<f64> {
+  if let Some(dpi) = unsafe { display.data::<Option<f64>>(XFT_DPI_KEY) } {
+    return unsafe { *dpi.as_ref() };
+  }
+  let dpi = read_resources(display).as_deref().and_then(parse_xft_dpi);
+  unsafe { display.set_data(XFT_DPI_KEY, dpi) };
+  dpi
+}
+
+/// Calls `f` when `Xft.dpi` changes, after dropping the cached value.
+///
+/// Desktops publish the DPI through both XSETTINGS and the X resource database, GTK notifies about
+/// the former.
+pub(crate) fn connect_xft_dpi_changed(
+  display: &gdk::Display,
+  f: impl Fn() + 'static,
+) -> Option<(gtk::Settings, glib::SignalHandlerId)> {
+  if !display.backend().is_x11() {
+    return None;
+  }
+  let settings = gtk::Settings::for_screen(&display.default_screen())?;
+  let display = display.downgrade();
+  let handler = settings.connect_notify_local(Some("gtk-xft-dpi"), move |_, _| {
+    if let Some(display) = display.upgrade() {
+      let _ = unsafe { display.steal_data::<Option<f64>>(XFT_DPI_KEY) };
+    }
+    f();
+  });
+  Some((settings, handler))
+}
+
+/// Reads the X resource database from the root window. Unlike `XResourceManagerString`, which
+/// keeps the one read when the display was opened, this sees the changes made since.
+fn read_resources(display: &gdk::Display) -> Option<String> {
+  let xlib = ffi::Xlib::open().ok()?;
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+    if xdisplay.is_null() {
+      return None;
+    }
+    let xdisplay = xdisplay as *mut ffi::Display;
+
+    let mut actual_type = 0;
+    let mut actual_format = 0;
+    let mut nitems = 0;
+    let mut bytes_after = 0;
+    let mut data = ptr::null_mut();
+    let status = (xlib.XGetWindowProperty)(
+      xdisplay,
+      (xlib.XDefaultRootWindow)(xdisplay),
+      ffi::XA_RESOURCE_MANAGER,
+      0,
+      c_long::MAX,
+      ffi::False,
+      ffi::XA_STRING,
+      &mut actual_type,
+      &mut actual_format,
+      &mut nitems,
+      &mut bytes_after,
+      &mut data,
+    );
+    if status != ffi::Success as c_int || data.is_null() {
+      return None;
+    }
+    let resources = (actual_format == 8)
+      .then(|| String::from_utf8_lossy(slice::from_raw_parts(data, nitems as usize)).into_owned());
+    (xlib.XFree)(data as *mut _);
+    resources
+  }
+}
+
+/// Finds `Xft.dpi` in an X resource database.
+fn parse_xft_dpi(resources: &str) -> Option<f64> {
+  resources.lines().find_map(|line| {
+    let (name, value) = line.split_once(':')?;
+    (name.trim() == "Xft.dpi")
+      .then(|| value.trim().parse().ok())
+      .flatten()
+  })
+}
+
+/// Derives a scale factor from the monitor's pixel density, in steps of 1/12th.
+fn physical_scale_factor(monitor: &gdk::Monitor) -> Option<f64> {
+  let (width_mm, height_mm) = (monitor.width_mm(), monitor.height_mm());
+  // Projectors and some TVs report no or made up sizes.
+  if width_mm <= 0 || height_mm <= 0 {
+    return None;
+  }
+  let geometry = monitor.geometry();
+  let gdk_scale = monitor.scale_factor() as f64;
+  let (width, height) = (
+    geometry.width() as f64 * gdk_scale,
+    geometry.height() as f64 * gdk_scale,
+  );
+
+  let pixels_per_mm = ((width * height) / (width_mm as f64 * height_mm as f64)).sqrt();
+  let scale_factor = (pixels_per_mm * 25.4 / 96.0 * 12.0).round() / 12.0;
+  (1.0..=4.0).contains(&scale_factor).then_some(scale_factor)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse_xft_dpi, ScaleFactorOverride};
+  use std::collections::HashMap;
+
+  #[test]
+  fn parse_override() {
+    assert_eq!(
+      ScaleFactorOverride::parse("1.5"),
+      Some(ScaleFactorOverride::Global(1.5))
+    );
+    assert_eq!(
+      ScaleFactorOverride::parse("DP-1=2;HDMI-1=1.25"),
+      Some(ScaleFactorOverride::PerMonitor(HashMap::from([
+        ("DP-1".to_string(), 2.0),
+        ("HDMI-1".to_string(), 1.25),
+      ])))
+    );
+    assert_eq!(
+      ScaleFactorOverride::parse(" DP-1 = 2 ;"),
+      Some(ScaleFactorOverride::PerMonitor(HashMap::from([(
+        "DP-1".to_string(),
+        2.0
+      )])))
+    );
+
+    assert_eq!(ScaleFactorOverride::parse(""), None);
+    assert_eq!(ScaleFactorOverride::parse("big"), None);
+    assert_eq!(ScaleFactorOverride::parse("0"), None);
+    assert_eq!(ScaleFactorOverride::parse("-1.5"), None);
+    assert_eq!(ScaleFactorOverride::parse("DP-1=2;HDMI-1"), None);
+    assert_eq!(ScaleFactorOverride::parse("DP-1=x"), None);
+  }
+
+  #[test]
+  fn parse_resources() {
+    assert_eq!(
+      parse_xft_dpi("Xcursor.size:\t24\nXft.dpi:\t144\nXft.hinting:\t1\n"),
+      Some(144.0)
+    );
+    assert_eq!(parse_xft_dpi("Xft.antialias:\t1\n"), None);
+    assert_eq!(parse_xft_dpi("Xft.dpi:\tlarge\n"), None);
+  }
+}
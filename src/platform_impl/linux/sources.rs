@@ -0,0 +1,287 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  marker::PhantomData,
+  os::unix::io::RawFd,
+  rc::Rc,
+  sync::atomic::{AtomicU64, Ordering},
+  time::Duration,
+};
+
+use gtk::glib::{self, IOCondition, SourceId};
+
+use super::user_events::EventSender;
+use crate::event::{Event, FdWatchId, Interest, TimerId};
+
+/// A file descriptor watch created with `EventLoopWindowTargetExtUnix::watch_fd`.
+///
+/// The watch is removed when this is dropped, which must happen on the event loop thread.
+#[derive(Debug)]
+pub struct FdWatch {
+  id: FdWatchId,
+  watches: FdWatches,
+  // The GLib source owns a closure that must be dropped on the event loop thread.
+  _marker: PhantomData<*mut ()>,
+}
+
+impl FdWatch {
+  /// The identifier carried by [`Event::FdReady`] for this watch.
+  pub fn id(&self) -> FdWatchId {
+    self.id
+  }
+}
+
+impl Drop for FdWatch {
+  fn drop(&mut self) {
+    let watch = self.watches.borrow_mut().remove(&self.id);
+    if let Some(source) = watch.and_then(|watch| watch.source) {
+      source.remove();
+    }
+  }
+}
+
+/// A file descriptor watched by the event loop.
+pub(crate) struct WatchedFd {
+  /// `None` while the `Event::FdReady` of the watch waits to be delivered.
+  source: Option<SourceId>,
+  /// Whether the file descriptor hung up or failed, it isn't watched anymore then.
+  closed: bool,
+  arm: Rc<dyn Fn() -> SourceId>,
+}
+
+impl std::fmt::Debug for WatchedFd {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("WatchedFd")
+      .field("source", &self.source)
+      .field("closed", &self.closed)
+      .finish_non_exhaustive()
+  }
+}
+
+/// The file descriptor watches that haven't been dropped yet.
+pub(crate) type FdWatches = Rc<RefCell<HashMap<FdWatchId, WatchedFd>>>;
+
+pub(crate) fn watch_fd<T: 'static>(
+  watches: &FdWatches,
+  fd: RawFd,
+  interest: Interest,
+  event_tx: EventSender<T>,
+) -> FdWatch {
+  static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+  let id = FdWatchId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+  let mut condition = IOCondition::HUP | IOCondition::ERR;
+  if interest.contains(Interest::READ) {
+    condition |= IOCondition::IN | IOCondition::PRI;
+  }
+  if interest.contains(Interest::WRITE) {
+    condition |= IOCondition::OUT;
+  }
+
+  let watches_ = Rc::downgrade(watches);
+  let arm = Rc::new(move || {
+    let watches = watches_.clone();
+    let event_tx = event_tx.clone();
+    // The source is level-triggered and would fire on every iteration until the file descriptor
+    // is read or written, so it is disarmed until the event is delivered.
+    glib::unix_fd_add_local(fd, condition, move |_, condition| {
+      if let Some(watches) = watches.upgrade() {
+        if let Some(watch) = watches.borrow_mut().get_mut(&id) {
+          watch.source = None;
+          // A hung up or failed file descriptor stays ready forever, so it is only reported once.
+          watch.closed = is_closed(condition);
+        }
+      }
+
+      if let Err(e) = event_tx.send(Event::FdReady {
+        watch_id: id,
+        readiness: readiness(condition, interest),
+      }) {
+        log::warn!("Failed to send fd ready event to event channel: {}", e);
+      }
+      glib::ControlFlow::Break
+    })
+  });
+  let source = arm();
+  watches.borrow_mut().insert(
+    id,
+    WatchedFd {
+      source: Some(source),
+      closed: false,
+      arm,
+    },
+  );
+
+  FdWatch {
+    id,
+    watches: watches.clone(),
+    _marker: PhantomData,
+  }
+}
+
+/// Watches the file descriptor of `id` again once its `Event::FdReady` was delivered.
+pub(crate) fn rearm_fd_watch(watches: &FdWatches, id: FdWatchId) {
+  let mut watches = watches.borrow_mut();
+  if let Some(watch) = watches.get_mut(&id) {
+    if watch.source.is_none() && !watch.closed {
+      watch.source = Some((watch.arm)());
+    }
+  }
+}
+
+/// Removes the sources of all watches, which outlive the event loop otherwise.
+pub(crate) fn remove_fd_watches(watches: &FdWatches) {
+  for (_, watch) in watches.borrow_mut().drain() {
+    if let Some(source) = watch.source {
+      source.remove();
+    }
+  }
+}
+
+fn readiness(condition: IOCondition, interest: Interest) -> Interest {
+  let mut readiness = Interest::empty();
+  if condition.intersects(IOCondition::IN | IOCondition::PRI) {
+    readiness |= Interest::READ;
+  }
+  if condition.contains(IOCondition::OUT) {
+    readiness |= Interest::WRITE;
+  }
+  // Let the next read or write on the file descriptor surface the hang-up or error.
+  if is_closed(condition) {
+    readiness |= interest;
+  }
+  readiness
+}
+
+fn is_closed(condition: IOCondition) -> bool {
+  condition.intersects(IOCondition::HUP | IOCondition::ERR)
+}
+
+/// The GLib sources of the timers that haven't expired or been cancelled yet.
+pub(crate) type Timers = Rc<RefCell<HashMap<TimerId, SourceId>>>;
+
+pub(crate) fn add_timer<T: 'static>(
+  timers: &Timers,
+  duration: Duration,
+  repeating: bool,
+  event_tx: EventSender<T>,
+) -> TimerId {
+  static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+  let id = TimerId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+  let timers_ = timers.clone();
+  let source = glib::timeout_add_local(duration, move || {
+    if let Err(e) = event_tx.send(Event::Timer { timer_id: id }) {
+      log::warn!("Failed to send timer event to event channel: {}", e);
+    }
+    if repeating {
+      glib::ControlFlow::Continue
+    } else {
+      // The source is destroyed by returning `Break`, so it must not be removed again.
+      timers_.borrow_mut().remove(&id);
+      glib::ControlFlow::Break
+    }
+  });
+  timers.borrow_mut().insert(id, source);
+
+  id
+}
+
+pub(crate) fn cancel_timer(timers: &Timers, id: TimerId) {
+  if let Some(source) = timers.borrow_mut().remove(&id) {
+    source.remove();
+  }
+}
+
+/// Removes the sources of all timers, which outlive the event loop otherwise.
+pub(crate) fn remove_timers(timers: &Timers) {
+  for (_, source) in timers.borrow_mut().drain() {
+    source.remove();
+  }
+}
+
+pub(crate) fn add_signals<T: 'static>(signals: &[i32], event_tx: &EventSender<T>) -> Vec<SourceId> {
+  supported_signals(signals)
+    .into_iter()
+    .map(|signal| {
+      let event_tx = event_tx.clone();
+      glib::unix_signal_add_local(signal, move || {
+        if let Err(e) = event_tx.send(Event::Signal { signal }) {
+          log::warn!("Failed to send signal event to event channel: {}", e);
+        }
+        glib::ControlFlow::Continue
+      })
+    })
+    .collect()
+}
+
+/// The signals in `signals` that can be delivered as events, the others are skipped with a
+/// warning.
+fn supported_signals(signals: &[i32]) -> Vec<i32> {
+  // The only signals `g_unix_signal_source_new` accepts.
+  const SUPPORTED: [i32; 6] = [
+    libc::SIGHUP,
+    libc::SIGINT,
+    libc::SIGTERM,
+    libc::SIGUSR1,
+    libc::SIGUSR2,
+    libc::SIGWINCH,
+  ];
+
+  signals
+    .iter()
+    .copied()
+    .filter(|signal| {
+      let supported = SUPPORTED.contains(signal);
+      if !supported {
+        log::warn!(
+          "Signal {} can't be delivered as an event, ignoring it",
+          signal
+        );
+      }
+      supported
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{is_closed, readiness, supported_signals};
+  use crate::event::Interest;
+  use gtk::glib::IOCondition;
+
+  #[test]
+  fn fd_readiness() {
+    assert_eq!(readiness(IOCondition::IN, Interest::READ), Interest::READ);
+    assert_eq!(
+      readiness(IOCondition::IN | IOCondition::OUT, Interest::all()),
+      Interest::all()
+    );
+    assert!(!is_closed(IOCondition::IN));
+
+    // Hang-ups and errors are surfaced through the interest of the watch.
+    assert_eq!(
+      readiness(IOCondition::HUP, Interest::WRITE),
+      Interest::WRITE
+    );
+    assert!(is_closed(IOCondition::HUP));
+    assert!(is_closed(IOCondition::ERR | IOCondition::IN));
+  }
+
+  #[test]
+  fn unsupported_signals_are_skipped() {
+    assert_eq!(
+      supported_signals(&[libc::SIGTERM, libc::SIGCHLD, libc::SIGINT, libc::SIGKILL]),
+      [libc::SIGTERM, libc::SIGINT]
+    );
+    assert_eq!(
+      supported_signals(&[libc::SIGHUP, libc::SIGUSR1, libc::SIGUSR2, libc::SIGWINCH]),
+      [libc::SIGHUP, libc::SIGUSR1, libc::SIGUSR2, libc::SIGWINCH]
+    );
+    assert!(supported_signals(&[libc::SIGCHLD, libc::SIGSEGV]).is_empty());
+  }
+}
@@ -0,0 +1,466 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+  cell::RefCell,
+  collections::VecDeque,
+  fmt,
+  future::Future,
+  num::NonZeroUsize,
+  pin::Pin,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Condvar, Mutex, MutexGuard,
+  },
+  task::{Context, Poll, Waker},
+};
+
+use crossbeam_channel::SendError;
+use gtk::glib::MainContext;
+
+use crate::{
+  event::Event,
+  event_loop::{EventLoopClosed, TrySendError},
+};
+
+/// When user events are delivered relative to the events coming from the system.
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UserEventPriority {
+  /// Delivered before the pending window and device events.
+  High,
+  /// Delivered in the order they were sent in, interleaved with the window and device events.
+  #[default]
+  Normal,
+  /// Delivered once the pending window and device events have been delivered.
+  Low,
+}
+
+/// Creates the channel of the events coming from the system.
+pub(crate) fn channel<T>() -> (EventSender<T>, EventReceiver<T>) {
+  let (tx, rx) = crossbeam_channel::unbounded();
+  (
+    EventSender {
+      tx,
+      sequence: Default::default(),
+    },
+    EventReceiver {
+      rx,
+      next: RefCell::new(None),
+    },
+  )
+}
+
+/// Sends events coming from the system to the event loop.
+///
+/// Events are numbered in the same sequence as user events, so that user events of
+/// [`UserEventPriority::Normal`] can be delivered in order with them.
+pub(crate) struct EventSender<T: 'static> {
+  tx: crossbeam_channel::Sender<(u64, Event<'static, T>)>,
+  sequence: Arc<AtomicU64>,
+}
+
+impl<T> EventSender<T> {
+  /// Sends `event`, failing once the event loop is dropped. The event is dropped then.
+  pub(crate) fn send(&self, event: Event<'static, T>) -> Result<(), SendError<()>> {
+    let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+    self.tx.send((sequence, event)).map_err(|_| SendError(()))
+  }
+}
+
+impl<T> Clone for EventSender<T> {
+  fn clone(&self) -> Self {
+    Self {
+      tx: self.tx.clone(),
+      sequence: self.sequence.clone(),
+    }
+  }
+}
+
+impl<T> fmt::Debug for EventSender<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("EventSender").finish_non_exhaustive()
+  }
+}
+
+/// Receives the events sent through an [`EventSender`].
+pub(crate) struct EventReceiver<T: 'static> {
+  rx: crossbeam_channel::Receiver<(u64, Event<'static, T>)>,
+  /// The next event, taken out of the channel to look at its number.
+  next: RefCell<Option<(u64, Event<'static, T>)>>,
+}
+
+impl<T> EventReceiver<T> {
+  pub(crate) fn is_empty(&self) -> bool {
+    self.next.borrow().is_none() && self.rx.is_empty()
+  }
+
+  /// The number of the next event, if any.
+  pub(crate) fn next_sequence(&self) -> Option<u64> {
+    let mut next = self.next.borrow_mut();
+    if next.is_none() {
+      *next = self.rx.try_recv().ok();
+    }
+    next.as_ref().map(|(sequence, _)| *sequence)
+  }
+
+  pub(crate) fn try_recv(&self) -> Option<Event<'static, T>> {
+    let next = self.next.borrow_mut().take();
+    next
+      .or_else(|| self.rx.try_recv().ok())
+      .map(|(_, event)| event)
+  }
+}
+
+/// The queue of user events shared between the event loop and its proxies.
+pub(crate) struct UserEventQueue<T> {
+  capacity: Option<NonZeroUsize>,
+  default_priority: UserEventPriority,
+  /// Shared with the [`EventSender`] of the event loop.
+  sequence: Arc<AtomicU64>,
+  state: Mutex<State<T>>,
+  /// Notified when an event is taken out of the queue or the queue is closed.
+  space: Condvar,
+}
+
+struct Pending<T> {
+  sequence: u64,
+  /// The coalescing key of the event, if any.
+  key: Option<u64>,
+  event: T,
+}
+
+struct State<T> {
+  /// Pending events of each priority.
+  high: VecDeque<Pending<T>>,
+  normal: VecDeque<Pending<T>>,
+  low: VecDeque<Pending<T>>,
+  /// Async senders waiting for space in the queue.
+  waiters: Vec<Waker>,
+  closed: bool,
+}
+
+impl<T> State<T> {
+  fn len(&self) -> usize {
+    self.high.len() + self.normal.len() + self.low.len()
+  }
+
+  fn events(&mut self, priority: UserEventPriority) -> &mut VecDeque<Pending<T>> {
+    match priority {
+      UserEventPriority::High => &mut self.high,
+      UserEventPriority::Normal => &mut self.normal,
+      UserEventPriority::Low => &mut self.low,
+    }
+  }
+}
+
+impl<T> UserEventQueue<T> {
+  pub(crate) fn new(
+    capacity: Option<NonZeroUsize>,
+    default_priority: UserEventPriority,
+    event_tx: &EventSender<T>,
+  ) -> Self {
+    Self {
+      capacity,
+      default_priority,
+      sequence: event_tx.sequence.clone(),
+      state: Mutex::new(State {
+        high: VecDeque::new(),
+        normal: VecDeque::new(),
+        low: VecDeque::new(),
+        waiters: Vec::new(),
+        closed: false,
+      }),
+      space: Condvar::new(),
+    }
+  }
+
+  pub(crate) fn default_priority(&self) -> UserEventPriority {
+    self.default_priority
+  }
+
+  fn lock(&self) -> MutexGuard<'_, State<T>> {
+    self.state.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  fn is_full(&self, state: &State<T>) -> bool {
+    self
+      .capacity
+      .is_some_and(|capacity| state.len() >= capacity.get())
+  }
+
+  fn push(
+    &self,
+    mut state: MutexGuard<'_, State<T>>,
+    key: Option<u64>,
+    priority: UserEventPriority,
+    event: T,
+  ) {
+    let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+    state.events(priority).push_back(Pending {
+      sequence,
+      key,
+      event,
+    });
+    drop(state);
+    MainContext::default().wakeup();
+  }
+
+  /// Queues `event`, blocking while the queue is full.
+  pub(crate) fn send(
+    &self,
+    priority: UserEventPriority,
+    event: T,
+  ) -> Result<(), EventLoopClosed<T>> {
+    let mut state = self.lock();
+    loop {
+      if state.closed {
+        return Err(EventLoopClosed(event));
+      }
+      if !self.is_full(&state) {
+        break;
+      }
+      state = self.space.wait(state).unwrap_or_else(|e| e.into_inner());
+    }
+    self.push(state, None, priority, event);
+    Ok(())
+  }
+
+  /// Queues `event` without blocking. With a `key`, the event replaces a pending event with the
+  /// same key instead, which always succeeds and keeps the priority of the pending event.
+  pub(crate) fn try_send(
+    &self,
+    key: Option<u64>,
+    priority: UserEventPriority,
+    event: T,
+  ) -> Result<(), TrySendError<T>> {
+    let mut state = self.lock();
+    if state.closed {
+      return Err(TrySendError::Closed(event));
+    }
+    if let Some(key) = key {
+      let state = &mut *state;
+      if let Some(pending) = state
+        .high
+        .iter_mut()
+        .chain(state.normal.iter_mut())
+        .chain(state.low.iter_mut())
+        .find(|pending| pending.key == Some(key))
+      {
+        pending.event = event;
+        return Ok(());
+      }
+    }
+    if self.is_full(&state) {
+      return Err(TrySendError::Full(event));
+    }
+    self.push(state, key, priority, event);
+    Ok(())
+  }
+
+  /// Takes the oldest pending event of the given priority. Events of
+  /// [`UserEventPriority::Normal`] are only taken if they were sent before the event numbered
+  /// `next_event`, the next one coming from the system.
+  pub(crate) fn pop(&self, priority: UserEventPriority, next_event: Option<u64>) -> Option<T> {
+    let mut state = self.lock();
+    let events = state.events(priority);
+    let in_order = match (priority, events.front(), next_event) {
+      (UserEventPriority::Normal, Some(pending), Some(next_event)) => pending.sequence < next_event,
+      _ => true,
+    };
+    if !in_order {
+      return None;
+    }
+    let Pending { event, .. } = events.pop_front()?;
+    let waiters = std::mem::take(&mut state.waiters);
+    drop(state);
+    self.space.notify_all();
+    waiters.into_iter().for_each(Waker::wake);
+    Some(event)
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.lock().len() == 0
+  }
+
+  /// Drops the pending events and makes every send fail from now on.
+  pub(crate) fn close(&self) {
+    let mut state = self.lock();
+    state.closed = true;
+    state.high.clear();
+    state.normal.clear();
+    state.low.clear();
+    let waiters = std::mem::take(&mut state.waiters);
+    drop(state);
+    self.space.notify_all();
+    waiters.into_iter().for_each(Waker::wake);
+  }
+}
+
+impl<T> fmt::Debug for UserEventQueue<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("UserEventQueue")
+      .field("capacity", &self.capacity)
+      .finish_non_exhaustive()
+  }
+}
+
+/// The future returned by `EventLoopProxyExtUnix::send_event_async`.
+///
+/// Resolves once the event is queued, waiting for space if the queue is full.
+#[must_use = "futures do nothing unless polled"]
+pub struct SendEvent<T> {
+  queue: Arc<UserEventQueue<T>>,
+  priority: UserEventPriority,
+  event: Option<T>,
+}
+
+impl<T> SendEvent<T> {
+  pub(crate) fn new(queue: Arc<UserEventQueue<T>>, priority: UserEventPriority, event: T) -> Self {
+    Self {
+      queue,
+      priority,
+      event: Some(event),
+    }
+  }
+}
+
+// The event is only ever moved out, never pinned.
+impl<T> Unpin for SendEvent<T> {}
+
+impl<T> Future for SendEvent<T> {
+  type Output = Result<(), EventLoopClosed<T>>;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = &mut *self;
+    let event = this
+      .event
+      .take()
+      .expect("`SendEvent` polled after completion");
+    let mut state = this.queue.lock();
+    if state.closed {
+      Poll::Ready(Err(EventLoopClosed(event)))
+    } else if this.queue.is_full(&state) {
+      state.waiters.push(cx.waker().clone());
+      this.event = Some(event);
+      Poll::Pending
+    } else {
+      this.queue.push(state, None, this.priority, event);
+      Poll::Ready(Ok(()))
+    }
+  }
+}
+
+impl<T> fmt::Debug for SendEvent<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("SendEvent").finish_non_exhaustive()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{channel, UserEventPriority, UserEventQueue};
+  use crate::{
+    event::Event,
+    event_loop::{EventLoopClosed, TrySendError},
+  };
+  use std::{num::NonZeroUsize, sync::Arc, thread, time::Duration};
+
+  fn queue(capacity: usize) -> UserEventQueue<u32> {
+    let (event_tx, _) = channel();
+    UserEventQueue::new(
+      NonZeroUsize::new(capacity),
+      UserEventPriority::Low,
+      &event_tx,
+    )
+  }
+
+  #[test]
+  fn capacity_bounds_pending_events() {
+    let queue = queue(2);
+    assert_eq!(queue.try_send(None, UserEventPriority::Low, 1), Ok(()));
+    assert_eq!(queue.try_send(None, UserEventPriority::High, 2), Ok(()));
+    assert_eq!(
+      queue.try_send(None, UserEventPriority::Low, 3),
+      Err(TrySendError::Full(3))
+    );
+
+    assert_eq!(queue.pop(UserEventPriority::High, None), Some(2));
+    assert_eq!(queue.try_send(None, UserEventPriority::Low, 3), Ok(()));
+    assert_eq!(queue.pop(UserEventPriority::Low, None), Some(1));
+    assert_eq!(queue.pop(UserEventPriority::Low, None), Some(3));
+    assert!(queue.is_empty());
+  }
+
+  #[test]
+  fn coalesced_events_replace_in_place() {
+    let queue = queue(2);
+    assert_eq!(queue.try_send(Some(7), UserEventPriority::High, 1), Ok(()));
+    assert_eq!(queue.try_send(None, UserEventPriority::Low, 2), Ok(()));
+
+    // Replacing succeeds on a full queue and keeps the position and priority of the pending event.
+    assert_eq!(queue.try_send(Some(7), UserEventPriority::Low, 3), Ok(()));
+    assert_eq!(queue.pop(UserEventPriority::High, None), Some(3));
+    assert_eq!(queue.pop(UserEventPriority::High, None), None);
+    assert_eq!(queue.pop(UserEventPriority::Low, None), Some(2));
+  }
+
+  #[test]
+  fn close_wakes_blocked_senders() {
+    let queue = Arc::new(queue(1));
+    assert_eq!(queue.send(UserEventPriority::Low, 1), Ok(()));
+
+    let sender = {
+      let queue = queue.clone();
+      thread::spawn(move || queue.send(UserEventPriority::Low, 2))
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert!(!sender.is_finished());
+
+    queue.close();
+    assert_eq!(sender.join().unwrap(), Err(EventLoopClosed(2)));
+    assert!(queue.is_empty());
+    assert_eq!(
+      queue.try_send(None, UserEventPriority::Low, 3),
+      Err(TrySendError::Closed(3))
+    );
+  }
+
+  #[test]
+  fn normal_events_keep_their_order() {
+    let (event_tx, events) = channel::<u32>();
+    let queue = UserEventQueue::new(None, UserEventPriority::Normal, &event_tx);
+    event_tx.send(Event::Suspended).unwrap();
+    assert_eq!(queue.try_send(None, UserEventPriority::Normal, 1), Ok(()));
+    assert_eq!(queue.try_send(None, UserEventPriority::High, 2), Ok(()));
+    event_tx.send(Event::Resumed).unwrap();
+    assert_eq!(queue.try_send(None, UserEventPriority::Low, 3), Ok(()));
+
+    // High priority events skip the events coming from the system.
+    assert_eq!(
+      queue.pop(UserEventPriority::High, events.next_sequence()),
+      Some(2)
+    );
+
+    // Normal ones wait for the system events sent before them.
+    assert_eq!(
+      queue.pop(UserEventPriority::Normal, events.next_sequence()),
+      None
+    );
+    assert!(matches!(events.try_recv(), Some(Event::Suspended)));
+    assert_eq!(
+      queue.pop(UserEventPriority::Normal, events.next_sequence()),
+      Some(1)
+    );
+    assert!(matches!(events.try_recv(), Some(Event::Resumed)));
+
+    // Low priority events wait for every system event.
+    assert!(events.is_empty());
+    assert_eq!(
+      queue.pop(UserEventPriority::Low, events.next_sequence()),
+      Some(3)
+    );
+    assert!(queue.is_empty());
+  }
+}
@@ -1,18 +1,416 @@
+use super::{scale, window::WindowId, DEVICE_ID};
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
   error::ExternalError,
-  window::WindowSizeConstraints,
+  event::{MouseButton, Pen, PenButtons, TouchPhase},
+  window::{TiledEdges, WindowSizeConstraints, WindowState},
 };
 use gtk::{
   gdk::{
     self,
-    prelude::{DeviceExt, SeatExt},
+    prelude::{DeviceExt, DeviceExtManual, SeatExt},
     Display,
   },
-  glib::{self},
-  traits::{GtkWindowExt, WidgetExt},
+  glib::{self, translate::ToGlibPtr, IsA},
+  traits::{GtkSettingsExt, GtkWindowExt, WidgetExt},
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{
+  cell::{Cell, RefCell},
+  collections::HashMap,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+/// Converts the time of a GDK event to an `Instant`.
+///
+/// X servers and Wayland compositors on Linux timestamp events with `CLOCK_MONOTONIC` in
+/// milliseconds, truncated to 32 bits, which is also the clock behind `Instant`. Times that don't
+/// look like they come from that clock fall back to the current time.
+pub fn event_time(time: u32) -> Instant {
+  // Older events than this are assumed to use another clock.
+  const MAX_AGE: Duration = Duration::from_secs(10);
+
+  let now = Instant::now();
+  let mut ts = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  if time == gdk::ffi::GDK_CURRENT_TIME as u32
+    || unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) } != 0
+  {
+    return now;
+  }
+
+  let monotonic = (ts.tv_sec as u64 * 1000 + ts.tv_nsec as u64 / 1_000_000) as u32;
+  let age = Duration::from_millis(monotonic.wrapping_sub(time) as u64);
+  if age > MAX_AGE {
+    now
+  } else {
+    now.checked_sub(age).unwrap_or(now)
+  }
+}
+
+/// Returns the positions of `device` relative to `window` between the GDK times `since` and
+/// `until`, both excluded, along with their time.
+pub fn motion_history(
+  device: &gdk::Device,
+  window: &gdk::Window,
+  since: u32,
+  until: u32,
+) -> Vec<(f64, f64, u32)> {
+  let samples = device
+    .history(window, since, until)
+    .into_iter()
+    .filter_map(|coord| {
+      let mut axes = *coord.axes();
+      let (mut x, mut y) = (0., 0.);
+      let found = device.is_axis(&mut axes, gdk::AxisUse::X, &mut x)
+        && device.is_axis(&mut axes, gdk::AxisUse::Y, &mut y);
+      found.then_some((x, y, coord.time()))
+    });
+  history_samples(samples, since, until)
+}
+
+/// Keeps the samples strictly between the GDK times `since` and `until` and orders them by time,
+/// accounting for the 32-bit event time wrapping around.
+fn history_samples(
+  samples: impl IntoIterator<Item = (f64, f64, u32)>,
+  since: u32,
+  until: u32,
+) -> Vec<(f64, f64, u32)> {
+  let span = until.wrapping_sub(since);
+  let mut samples: Vec<_> = samples
+    .into_iter()
+    .filter(|&(_, _, time)| {
+      let offset = time.wrapping_sub(since);
+      offset > 0 && offset < span
+    })
+    .collect();
+  samples.sort_by_key(|&(_, _, time)| time.wrapping_sub(since));
+  samples
+}
+
+/// Returns the button mask GDK uses for `button` in event states.
+fn button_mask(button: u32) -> gdk::ModifierType {
+  match button {
+    1 => gdk::ModifierType::BUTTON1_MASK,
+    2 => gdk::ModifierType::BUTTON2_MASK,
+    3 => gdk::ModifierType::BUTTON3_MASK,
+    4 => gdk::ModifierType::BUTTON4_MASK,
+    5 => gdk::ModifierType::BUTTON5_MASK,
+    _ => gdk::ModifierType::empty(),
+  }
+}
+
+/// Maps a GDK button number to a `MouseButton`.
+pub fn mouse_button(button: u32) -> MouseButton {
+  match button {
+    1 => MouseButton::Left,
+    2 => MouseButton::Middle,
+    3 => MouseButton::Right,
+    8 => MouseButton::Back,
+    9 => MouseButton::Forward,
+    _ => MouseButton::Other(button as u16),
+  }
+}
+
+/// The GDK window state flags reported in `WindowEvent::StateChanged`.
+const TRACKED_WINDOW_STATE: gdk::WindowState = gdk::WindowState::MAXIMIZED
+  .union(gdk::WindowState::ICONIFIED)
+  .union(gdk::WindowState::FULLSCREEN)
+  .union(gdk::WindowState::FOCUSED)
+  .union(gdk::WindowState::TILED)
+  .union(gdk::WindowState::TOP_TILED)
+  .union(gdk::WindowState::RIGHT_TILED)
+  .union(gdk::WindowState::BOTTOM_TILED)
+  .union(gdk::WindowState::LEFT_TILED)
+  .union(gdk::WindowState::TOP_RESIZABLE)
+  .union(gdk::WindowState::RIGHT_RESIZABLE)
+  .union(gdk::WindowState::BOTTOM_RESIZABLE)
+  .union(gdk::WindowState::LEFT_RESIZABLE)
+  .union(gdk::WindowState::STICKY)
+  .union(gdk::WindowState::ABOVE)
+  .union(gdk::WindowState::BELOW);
+
+/// Maps GDK window state flags to a `WindowState` snapshot.
+pub fn window_state(state: gdk::WindowState) -> WindowState {
+  WindowState {
+    maximized: state.contains(gdk::WindowState::MAXIMIZED),
+    minimized: state.contains(gdk::WindowState::ICONIFIED),
+    fullscreen: state.contains(gdk::WindowState::FULLSCREEN),
+    focused: state.contains(gdk::WindowState::FOCUSED),
+    tiled_edges: tiled_edges(state),
+    resizable_edges: resizable_edges(state),
+    sticky: state.contains(gdk::WindowState::STICKY),
+    above: state.contains(gdk::WindowState::ABOVE),
+    below: state.contains(gdk::WindowState::BELOW),
+  }
+}
+
+/// Returns the snapshot to report in `WindowEvent::StateChanged` for a window state event, if
+/// one of the flags it covers changed.
+pub fn window_state_change(
+  changed: gdk::WindowState,
+  new_state: gdk::WindowState,
+) -> Option<WindowState> {
+  changed
+    .intersects(TRACKED_WINDOW_STATE)
+    .then(|| window_state(new_state))
+}
+
+/// Maps GDK window state flags to the tiled edges, window managers without per edge tiling only
+/// report `TILED`, which means all edges are tiled.
+pub fn tiled_edges(state: gdk::WindowState) -> TiledEdges {
+  let mut edges = TiledEdges::empty();
+  edges.set(TiledEdges::TOP, state.contains(gdk::WindowState::TOP_TILED));
+  edges.set(
+    TiledEdges::RIGHT,
+    state.contains(gdk::WindowState::RIGHT_TILED),
+  );
+  edges.set(
+    TiledEdges::BOTTOM,
+    state.contains(gdk::WindowState::BOTTOM_TILED),
+  );
+  edges.set(
+    TiledEdges::LEFT,
+    state.contains(gdk::WindowState::LEFT_TILED),
+  );
+  if edges.is_empty() && state.contains(gdk::WindowState::TILED) {
+    edges = TiledEdges::all();
+  }
+  edges
+}
+
+/// Maps GDK window state flags to the edges the window can be resized from.
+fn resizable_edges(state: gdk::WindowState) -> TiledEdges {
+  let mut edges = TiledEdges::empty();
+  for (edge, flag) in [
+    (TiledEdges::TOP, gdk::WindowState::TOP_RESIZABLE),
+    (TiledEdges::RIGHT, gdk::WindowState::RIGHT_RESIZABLE),
+    (TiledEdges::BOTTOM, gdk::WindowState::BOTTOM_RESIZABLE),
+    (TiledEdges::LEFT, gdk::WindowState::LEFT_RESIZABLE),
+  ] {
+    edges.set(edge, state.contains(flag));
+  }
+  edges
+}
+
+bitflags! {
+  /// The reasons a window is occluded, it is visible again once none of them apply.
+  #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+  pub struct Occlusion: u8 {
+    /// The window is minimized.
+    const MINIMIZED = 1 << 0;
+    /// The window was unmapped by the window manager, e.g. it is on another workspace.
+    const WITHDRAWN = 1 << 1;
+    /// The window is fully covered by other windows.
+    const OBSCURED = 1 << 2;
+    /// The compositor stopped sending frame callbacks.
+    const NO_FRAMES = 1 << 3;
+  }
+}
+
+/// Tracks why each window is occluded.
+#[derive(Default, Clone)]
+pub struct OcclusionTracker(Rc<RefCell<HashMap<WindowId, Occlusion>>>);
+
+impl OcclusionTracker {
+  pub fn reasons(&self, id: WindowId) -> Occlusion {
+    self.0.borrow().get(&id).copied().unwrap_or_default()
+  }
+
+  /// Sets whether `reason` applies to the window, returns the new occlusion state if it changed.
+  pub fn update(&self, id: WindowId, reason: Occlusion, applies: bool) -> Option<bool> {
+    let mut windows = self.0.borrow_mut();
+    let reasons = windows.entry(id).or_default();
+    let was_occluded = !reasons.is_empty();
+    reasons.set(reason, applies);
+    let occluded = !reasons.is_empty();
+    (occluded != was_occluded).then_some(occluded)
+  }
+
+  pub fn remove(&self, id: WindowId) {
+    self.0.borrow_mut().remove(&id);
+  }
+}
+
+/// Counts the presses of a button made in a row, using the double-click time and distance from
+/// the GTK settings.
+#[derive(Default)]
+pub struct ClickCounter {
+  settings: Option<gtk::Settings>,
+  // Button, time and position of the last press.
+  last: Option<(u32, u32, f64, f64)>,
+  count: u32,
+}
+
+impl ClickCounter {
+  pub fn new() -> Self {
+    Self {
+      settings: gtk::Settings::default(),
+      ..Default::default()
+    }
+  }
+
+  /// Registers a press and returns its click count.
+  pub fn press(&mut self, button: u32, time: u32, (x, y): (f64, f64)) -> u32 {
+    // The settings object is kept, its properties follow the desktop settings.
+    let (max_time, max_distance) = self
+      .settings
+      .as_ref()
+      .map(|settings| {
+        (
+          settings.gtk_double_click_time(),
+          settings.gtk_double_click_distance(),
+        )
+      })
+      .unwrap_or((400, 5));
+    self.press_within(button, time, (x, y), max_time as u32, max_distance as f64)
+  }
+
+  /// Registers a press, which is in a row with the previous one if it is of the same button and
+  /// at most `max_time` milliseconds and `max_distance` pixels away.
+  fn press_within(
+    &mut self,
+    button: u32,
+    time: u32,
+    (x, y): (f64, f64),
+    max_time: u32,
+    max_distance: f64,
+  ) -> u32 {
+    let in_row = self
+      .last
+      .is_some_and(|(last_button, last_time, last_x, last_y)| {
+        last_button == button
+          && time.wrapping_sub(last_time) <= max_time
+          && (x - last_x).abs() <= max_distance
+          && (y - last_y).abs() <= max_distance
+      });
+    self.count = if in_row { self.count + 1 } else { 1 };
+    self.last = Some((button, time, x, y));
+    self.count
+  }
+
+  /// Returns the click count of the last press of `button`.
+  pub fn release(&self, button: u32) -> u32 {
+    match self.last {
+      Some((last_button, ..)) if last_button == button => self.count,
+      _ => 1,
+    }
+  }
+}
+
+/// Maps the raw phase of a touchpad gesture event.
+fn gesture_phase(phase: i32) -> TouchPhase {
+  match phase {
+    gdk::ffi::GDK_TOUCHPAD_GESTURE_PHASE_BEGIN => TouchPhase::Started,
+    gdk::ffi::GDK_TOUCHPAD_GESTURE_PHASE_END => TouchPhase::Ended,
+    gdk::ffi::GDK_TOUCHPAD_GESTURE_PHASE_CANCEL => TouchPhase::Cancelled,
+    _ => TouchPhase::Moved,
+  }
+}
+
+/// Returns the phase of a touchpad pinch event, gdk-rs has no accessor for it.
+pub fn pinch_phase(pinch: &gdk::EventTouchpadPinch) -> TouchPhase {
+  // SAFETY: `pinch` owns a valid `GdkEventTouchpadPinch` for the duration of the borrow.
+  gesture_phase(unsafe { (*pinch.to_glib_none().0).phase } as i32)
+}
+
+/// Returns the phase of a touchpad swipe event, gdk-rs has no accessor for it.
+pub fn swipe_phase(swipe: &gdk::EventTouchpadSwipe) -> TouchPhase {
+  // SAFETY: `swipe` owns a valid `GdkEventTouchpadSwipe` for the duration of the borrow.
+  gesture_phase(unsafe { (*swipe.to_glib_none().0).phase } as i32)
+}
+
+/// Turns the pinch scale GDK reports relative to the start of the gesture into the change since
+/// the previous event.
+pub struct PinchDeltas {
+  last_scale: Cell<f64>,
+}
+
+impl Default for PinchDeltas {
+  fn default() -> Self {
+    Self {
+      last_scale: Cell::new(1.0),
+    }
+  }
+}
+
+impl PinchDeltas {
+  /// Returns the scale and rotation deltas of a pinch event, both are `0.0` when it starts.
+  pub fn update(&self, phase: TouchPhase, scale: f64, angle_delta: f64) -> (f64, f64) {
+    let last_scale = self.last_scale.replace(scale);
+    match phase {
+      TouchPhase::Started => (0.0, 0.0),
+      _ => (scale - last_scale, angle_delta),
+    }
+  }
+}
+
+/// Returns the pen phase of a button press or release and the button state after it. Only the
+/// primary button, the tip touching the tablet, starts and ends a stroke.
+pub fn pen_button_change(
+  button: u32,
+  state: gdk::ModifierType,
+  pressed: bool,
+) -> (TouchPhase, gdk::ModifierType) {
+  match (button, pressed) {
+    (1, true) => (TouchPhase::Started, state | button_mask(button)),
+    (1, false) => (TouchPhase::Ended, state - button_mask(button)),
+    (_, true) => (TouchPhase::Moved, state | button_mask(button)),
+    (_, false) => (TouchPhase::Moved, state - button_mask(button)),
+  }
+}
+
+/// Maps the button state of a stylus to its barrel buttons, GDK reports them as the middle and
+/// right buttons.
+fn pen_buttons(buttons: gdk::ModifierType) -> PenButtons {
+  let mut pen_buttons = PenButtons::empty();
+  pen_buttons.set(
+    PenButtons::BARREL,
+    buttons.contains(gdk::ModifierType::BUTTON2_MASK),
+  );
+  pen_buttons.set(
+    PenButtons::SECONDARY_BARREL,
+    buttons.contains(gdk::ModifierType::BUTTON3_MASK),
+  );
+  pen_buttons
+}
+
+/// Builds a `Pen` from `event` if it comes from a stylus. `buttons` is the button state after the
+/// event.
+pub fn pen_event(
+  event: &gdk::Event,
+  phase: TouchPhase,
+  buttons: gdk::ModifierType,
+  scale_factor: f64,
+) -> Option<Pen> {
+  let mut eraser = match event.source_device()?.source() {
+    gdk::InputSource::Pen => false,
+    gdk::InputSource::Eraser => true,
+    _ => return None,
+  };
+  if let Some(tool) = event.device_tool() {
+    eraser |= tool.tool_type() == gdk::DeviceToolType::Eraser;
+  }
+
+  let (x, y) = event.coords()?;
+  Some(Pen {
+    device_id: DEVICE_ID,
+    phase,
+    location: LogicalPosition::new(x, y).to_physical(scale_factor),
+    contact: buttons.contains(gdk::ModifierType::BUTTON1_MASK),
+    pressure: event.axis(gdk::AxisUse::Pressure),
+    tilt: event
+      .axis(gdk::AxisUse::Xtilt)
+      .zip(event.axis(gdk::AxisUse::Ytilt)),
+    rotation: event.axis(gdk::AxisUse::Rotation),
+    distance: event.axis(gdk::AxisUse::Distance),
+    eraser,
+    buttons: pen_buttons(buttons),
+    timestamp: event_time(event.time()),
+  })
+}
 
 #[inline]
 pub fn cursor_position(is_wayland: bool) -> Result<PhysicalPosition<f64>, ExternalError> {
@@ -32,12 +430,12 @@ pub fn cursor_position(is_wayland: bool) -> Result<PhysicalPosition<f64>, Extern
           LogicalPosition::new(x, y).to_physical(g.scale_factor() as _)
         })
       })
-      .map(|p| p.ok_or(ExternalError::Os(os_error!(super::OsError))))
-      .ok_or(ExternalError::Os(os_error!(super::OsError)))?
+      .map(|p| p.ok_or(ExternalError::Os(os_error!(super::OsError::default()))))
+      .ok_or(ExternalError::Os(os_error!(super::OsError::default())))?
   }
 }
 
-pub fn set_size_constraints<W: GtkWindowExt + WidgetExt>(
+pub fn set_size_constraints<W: GtkWindowExt + IsA<gtk::Widget>>(
   window: &W,
   constraints: WindowSizeConstraints,
 ) {
@@ -49,10 +447,15 @@ pub fn set_size_constraints<W: GtkWindowExt + WidgetExt>(
     geom_mask |= gdk::WindowHints::MAX_SIZE;
   }
 
-  let scale_factor = window.scale_factor() as f64;
+  let scale_factor = scale::widget_scale_factor(window);
+  let gdk_scale = window.scale_factor() as f64;
 
-  let min_size: LogicalSize<i32> = constraints.min_size_logical(scale_factor);
-  let max_size: LogicalSize<i32> = constraints.max_size_logical(scale_factor);
+  let min_size: LogicalSize<i32> = constraints
+    .min_size_physical::<f64>(scale_factor)
+    .to_logical(gdk_scale);
+  let max_size: LogicalSize<i32> = constraints
+    .max_size_physical::<f64>(scale_factor)
+    .to_logical(gdk_scale);
 
   let picky_none: Option<&gtk::Window> = None;
   window.set_geometry_hints(
@@ -109,3 +512,284 @@ impl<W: GtkWindowExt + WidgetExt> WindowMaximizeProcess<W> {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    event_time, history_samples, mouse_button, pen_button_change, pen_buttons, tiled_edges,
+    window_state_change, ClickCounter, Occlusion, OcclusionTracker, PinchDeltas,
+  };
+  use crate::{
+    event::{MouseButton, PenButtons, TouchPhase},
+    platform_impl::platform::window::WindowId,
+    window::{TiledEdges, WindowState},
+  };
+  use gtk::gdk;
+  use std::time::{Duration, Instant};
+
+  #[test]
+  fn event_time_uses_monotonic_clock() {
+    let mut ts = libc::timespec {
+      tv_sec: 0,
+      tv_nsec: 0,
+    };
+    assert_eq!(
+      unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) },
+      0
+    );
+    let now_ms = (ts.tv_sec as u64 * 1000 + ts.tv_nsec as u64 / 1_000_000) as u32;
+
+    let before = Instant::now();
+    let age = before - event_time(now_ms.wrapping_sub(500));
+    assert!(age >= Duration::from_millis(450) && age <= Duration::from_millis(600));
+
+    // The current time, and times from another clock, fall back to now.
+    assert!(event_time(gdk::ffi::GDK_CURRENT_TIME as u32) >= before);
+    assert!(event_time(now_ms.wrapping_sub(3_600_000)) >= before);
+  }
+
+  #[test]
+  fn motion_history_is_ordered() {
+    let samples = [
+      (3.0, 3.0, 130),
+      (1.0, 1.0, 110),
+      (0.0, 0.0, 100),
+      (2.0, 2.0, 120),
+      (4.0, 4.0, 140),
+    ];
+    assert_eq!(
+      history_samples(samples, 100, 140),
+      vec![(1.0, 1.0, 110), (2.0, 2.0, 120), (3.0, 3.0, 130)]
+    );
+
+    // Samples on both sides of the event time wrapping around keep their order.
+    let samples = [(2.0, 2.0, 5), (1.0, 1.0, u32::MAX - 5), (0.0, 0.0, 20)];
+    assert_eq!(
+      history_samples(samples, u32::MAX - 10, 10),
+      vec![(1.0, 1.0, u32::MAX - 5), (2.0, 2.0, 5)]
+    );
+  }
+
+  #[test]
+  fn motion_history_timestamps_follow_event_times() {
+    let mut ts = libc::timespec {
+      tv_sec: 0,
+      tv_nsec: 0,
+    };
+    assert_eq!(
+      unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) },
+      0
+    );
+    let now_ms = (ts.tv_sec as u64 * 1000 + ts.tv_nsec as u64 / 1_000_000) as u32;
+
+    let since = now_ms.wrapping_sub(1000);
+    let samples = [
+      (0.0, 0.0, now_ms.wrapping_sub(200)),
+      (0.0, 0.0, now_ms.wrapping_sub(800)),
+    ];
+    let timestamps: Vec<_> = history_samples(samples, since, now_ms)
+      .into_iter()
+      .map(|(_, _, time)| event_time(time))
+      .collect();
+    assert_eq!(timestamps.len(), 2);
+    let gap = timestamps[1] - timestamps[0];
+    assert!(gap >= Duration::from_millis(590) && gap <= Duration::from_millis(610));
+  }
+
+  #[test]
+  fn pen_stroke_phases() {
+    // The tip touches the tablet while the barrel button is held.
+    let state = gdk::ModifierType::BUTTON2_MASK;
+    let (phase, state) = pen_button_change(1, state, true);
+    assert_eq!(phase, TouchPhase::Started);
+    assert!(state.contains(gdk::ModifierType::BUTTON1_MASK));
+    assert_eq!(pen_buttons(state), PenButtons::BARREL);
+
+    // Barrel buttons change during the stroke without ending it.
+    let (phase, state) = pen_button_change(3, state, true);
+    assert_eq!(phase, TouchPhase::Moved);
+    assert_eq!(
+      pen_buttons(state),
+      PenButtons::BARREL | PenButtons::SECONDARY_BARREL
+    );
+    let (phase, state) = pen_button_change(2, state, false);
+    assert_eq!(phase, TouchPhase::Moved);
+    assert_eq!(pen_buttons(state), PenButtons::SECONDARY_BARREL);
+
+    // Lifting the tip ends the stroke and the pen no longer has contact.
+    let (phase, state) = pen_button_change(1, state, false);
+    assert_eq!(phase, TouchPhase::Ended);
+    assert!(!state.contains(gdk::ModifierType::BUTTON1_MASK));
+    assert_eq!(pen_buttons(state), PenButtons::SECONDARY_BARREL);
+  }
+
+  #[test]
+  fn pinch_scale_accumulates() {
+    let pinch = PinchDeltas::default();
+    let mut scale = 1.0;
+    for (phase, gdk_scale, angle_delta) in [
+      (TouchPhase::Started, 1.0, 0.0),
+      (TouchPhase::Moved, 1.25, 0.1),
+      (TouchPhase::Moved, 1.5, -0.05),
+      (TouchPhase::Moved, 1.375, 0.0),
+      (TouchPhase::Ended, 1.375, 0.0),
+    ] {
+      let (scale_delta, rotation_delta) = pinch.update(phase, gdk_scale, angle_delta);
+      assert_eq!(rotation_delta, angle_delta);
+      scale += scale_delta;
+      assert_eq!(scale, gdk_scale);
+    }
+
+    // A new gesture starts from its own scale rather than where the previous one ended.
+    assert_eq!(pinch.update(TouchPhase::Started, 1.0, 0.2), (0.0, 0.0));
+    assert_eq!(pinch.update(TouchPhase::Moved, 0.75, 0.0), (-0.25, 0.0));
+  }
+
+  #[test]
+  fn tiled_implies_every_edge() {
+    assert_eq!(tiled_edges(gdk::WindowState::empty()), TiledEdges::empty());
+    assert_eq!(tiled_edges(gdk::WindowState::TILED), TiledEdges::all());
+    assert_eq!(
+      tiled_edges(gdk::WindowState::TILED | gdk::WindowState::LEFT_TILED),
+      TiledEdges::LEFT
+    );
+    assert_eq!(
+      tiled_edges(gdk::WindowState::TOP_TILED | gdk::WindowState::BOTTOM_TILED),
+      TiledEdges::TOP | TiledEdges::BOTTOM
+    );
+  }
+
+  #[test]
+  fn window_state_changes() {
+    let state = gdk::WindowState::FOCUSED | gdk::WindowState::RIGHT_TILED;
+
+    // Maximizing reports a full snapshot, including the flags that didn't change.
+    assert_eq!(
+      window_state_change(
+        gdk::WindowState::MAXIMIZED,
+        state | gdk::WindowState::MAXIMIZED,
+      ),
+      Some(WindowState {
+        maximized: true,
+        focused: true,
+        tiled_edges: TiledEdges::RIGHT,
+        ..Default::default()
+      })
+    );
+
+    // Untracked flags alone don't report a change.
+    assert_eq!(
+      window_state_change(
+        gdk::WindowState::WITHDRAWN,
+        state | gdk::WindowState::WITHDRAWN,
+      ),
+      None
+    );
+
+    // Losing the focus and a tiled edge at once is a single change.
+    assert_eq!(
+      window_state_change(state, gdk::WindowState::empty()),
+      Some(WindowState::default())
+    );
+  }
+
+  #[test]
+  fn resizable_edges_are_reported() {
+    // Snapping to the left half leaves only the right edge resizable.
+    let tiled = gdk::WindowState::TILED
+      | gdk::WindowState::LEFT_TILED
+      | gdk::WindowState::TOP_TILED
+      | gdk::WindowState::BOTTOM_TILED
+      | gdk::WindowState::RIGHT_RESIZABLE;
+    assert_eq!(
+      window_state_change(tiled, tiled),
+      Some(WindowState {
+        tiled_edges: TiledEdges::LEFT | TiledEdges::TOP | TiledEdges::BOTTOM,
+        resizable_edges: TiledEdges::RIGHT,
+        ..Default::default()
+      })
+    );
+
+    // A change of the edge constraints alone is reported too.
+    let state = tiled | gdk::WindowState::LEFT_RESIZABLE;
+    assert_eq!(
+      window_state_change(gdk::WindowState::LEFT_RESIZABLE, state)
+        .map(|state| state.resizable_edges),
+      Some(TiledEdges::LEFT | TiledEdges::RIGHT)
+    );
+  }
+
+  #[test]
+  fn side_buttons_are_back_and_forward() {
+    assert_eq!(mouse_button(1), MouseButton::Left);
+    assert_eq!(mouse_button(2), MouseButton::Middle);
+    assert_eq!(mouse_button(3), MouseButton::Right);
+    assert_eq!(mouse_button(8), MouseButton::Back);
+    assert_eq!(mouse_button(9), MouseButton::Forward);
+    assert_eq!(mouse_button(10), MouseButton::Other(10));
+  }
+
+  #[test]
+  fn click_count_resets_on_gaps() {
+    let mut clicks = ClickCounter::default();
+    let mut press = |button, time, position| clicks.press_within(button, time, position, 400, 5.0);
+
+    assert_eq!(press(1, 1000, (10.0, 10.0)), 1);
+    assert_eq!(press(1, 1300, (12.0, 8.0)), 2);
+    assert_eq!(press(1, 1600, (10.0, 10.0)), 3);
+    // Too late, too far, or another button start over.
+    assert_eq!(press(1, 2100, (10.0, 10.0)), 1);
+    assert_eq!(press(1, 2200, (20.0, 10.0)), 1);
+    assert_eq!(press(3, 2300, (20.0, 10.0)), 1);
+    // The event time wraps around.
+    assert_eq!(press(3, u32::MAX - 100, (20.0, 10.0)), 1);
+    assert_eq!(press(3, 100, (20.0, 10.0)), 2);
+
+    assert_eq!(clicks.release(3), 2);
+    assert_eq!(clicks.release(1), 1);
+  }
+
+  #[test]
+  fn click_count_thresholds_are_inclusive() {
+    let mut clicks = ClickCounter::default();
+    let mut press = |time, position| clicks.press_within(1, time, position, 400, 5.0);
+
+    // Exactly at the double-click time and distance still counts.
+    assert_eq!(press(1000, (10.0, 10.0)), 1);
+    assert_eq!(press(1400, (15.0, 5.0)), 2);
+    // One millisecond later starts over.
+    assert_eq!(press(1801, (15.0, 5.0)), 1);
+    // So does moving past the distance on either axis.
+    assert_eq!(press(1900, (15.0, 10.5)), 1);
+    assert_eq!(press(2000, (9.5, 10.5)), 1);
+    // The distance is measured from the previous press, not the first one.
+    assert_eq!(press(2100, (14.5, 10.5)), 2);
+    assert_eq!(press(2200, (19.5, 10.5)), 3);
+  }
+
+  #[test]
+  fn occlusion_tracks_every_reason() {
+    let occlusion = OcclusionTracker::default();
+    let id = WindowId(1);
+
+    assert_eq!(occlusion.update(id, Occlusion::MINIMIZED, true), Some(true));
+    assert_eq!(occlusion.update(id, Occlusion::NO_FRAMES, true), None);
+    assert_eq!(
+      occlusion.reasons(id),
+      Occlusion::MINIMIZED | Occlusion::NO_FRAMES
+    );
+    assert_eq!(occlusion.reasons(WindowId(2)), Occlusion::empty());
+
+    // Visible again only once no reason applies.
+    assert_eq!(occlusion.update(id, Occlusion::MINIMIZED, false), None);
+    assert_eq!(occlusion.update(id, Occlusion::MINIMIZED, false), None);
+    assert_eq!(
+      occlusion.update(id, Occlusion::NO_FRAMES, false),
+      Some(false)
+    );
+
+    occlusion.update(id, Occlusion::OBSCURED, true);
+    occlusion.remove(id);
+    assert_eq!(occlusion.reasons(id), Occlusion::empty());
+  }
+}
@@ -26,15 +26,15 @@ use crate::{
   monitor::MonitorHandle as RootMonitorHandle,
   platform_impl::wayland::header::WlHeader,
   window::{
-    CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, UserAttentionType,
-    WindowAttributes, WindowSizeConstraints, RGBA,
+    CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, TiledEdges,
+    UserAttentionType, WindowAttributes, WindowSizeConstraints, RGBA,
   },
 };
 
 use super::{
   event_loop::EventLoopWindowTarget,
   monitor::{self, MonitorHandle},
-  util, Parent, PlatformSpecificWindowBuilderAttributes,
+  scale, util, Parent, PlatformSpecificWindowBuilderAttributes,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -68,8 +68,6 @@ pub struct Window {
   minimized: Rc<AtomicBool>,
   fullscreen: RefCell<Option<Fullscreen>>,
   inner_size_constraints: RefCell<WindowSizeConstraints>,
-  /// Draw event Sender
-  draw_tx: crossbeam_channel::Sender<WindowId>,
   preferred_theme: RefCell<Option<Theme>>,
   css_provider: CssProvider,
 }
@@ -82,7 +80,6 @@ impl Window {
   ) -> Result<Self, RootOsError> {
     let app = &event_loop_window_target.app;
     let window_requests_tx = event_loop_window_target.window_requests_tx.clone();
-    let draw_tx = event_loop_window_target.draw_tx.clone();
     let is_wayland = event_loop_window_target.is_wayland();
 
     let mut window_builder = gtk::ApplicationWindow::builder()
@@ -106,9 +103,10 @@ impl Window {
 
     // Set Width/Height & Resizable
     let win_scale_factor = window.scale_factor();
+    let scale_factor = scale::widget_scale_factor(&window);
     let (width, height) = attributes
       .inner_size
-      .map(|size| size.to_logical::<f64>(win_scale_factor as f64).into())
+      .map(|size| scale::size_to_gdk::<f64>(size, scale_factor, win_scale_factor).into())
       .unwrap_or((800, 600));
     window.set_default_size(1, 1);
     window.resize(width, height);
@@ -130,7 +128,8 @@ impl Window {
 
     // Set Position
     if let Some(position) = attributes.position {
-      let (x, y): (i32, i32) = position.to_logical::<i32>(win_scale_factor as f64).into();
+      let (x, y): (i32, i32) =
+        scale::position_to_gdk::<i32>(position, scale_factor, win_scale_factor).into();
       window.move_(x, y);
     }
 
@@ -257,6 +256,8 @@ impl Window {
         transparent,
         fullscreen: attributes.fullscreen.is_some(),
         cursor_moved,
+        motion_compression: pl_attribs.motion_compression,
+        motion_history: pl_attribs.motion_history,
       },
     )) {
       log::warn!("Fail to send wire up events request: {}", e);
@@ -282,7 +283,6 @@ impl Window {
       window,
       default_vbox,
       window_requests_tx,
-      draw_tx,
       scale_factor,
       outer_position,
       inner_position,
@@ -401,7 +401,6 @@ impl Window {
     window: gtk::ApplicationWindow,
   ) -> Result<Self, RootOsError> {
     let window_requests_tx = event_loop_window_target.window_requests_tx.clone();
-    let draw_tx = event_loop_window_target.draw_tx.clone();
 
     let window_id = WindowId(window.id());
     event_loop_window_target
@@ -425,7 +424,6 @@ impl Window {
       window,
       default_vbox: None,
       window_requests_tx,
-      draw_tx,
       scale_factor,
       outer_position,
       inner_position,
@@ -448,12 +446,15 @@ impl Window {
   }
 
   pub fn scale_factor(&self) -> f64 {
-    self.scale_factor.load(Ordering::Acquire) as f64
+    scale::widget_scale_factor(&self.window)
   }
 
   pub fn request_redraw(&self) {
-    if let Err(e) = self.draw_tx.send(self.window_id) {
-      log::warn!("Failed to send redraw event to event channel: {}", e);
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Redraw))
+    {
+      log::warn!("Fail to send redraw request: {}", e);
     }
   }
 
@@ -474,10 +475,12 @@ impl Window {
   }
 
   pub fn set_outer_position<P: Into<Position>>(&self, position: P) {
-    let (x, y): (i32, i32) = position
-      .into()
-      .to_logical::<i32>(self.scale_factor())
-      .into();
+    let (x, y): (i32, i32) = scale::position_to_gdk::<i32>(
+      position.into(),
+      self.scale_factor(),
+      self.scale_factor.load(Ordering::Acquire),
+    )
+    .into();
 
     if let Err(e) = self
       .window_requests_tx
@@ -507,7 +510,12 @@ impl Window {
   }
 
   pub fn set_inner_size<S: Into<Size>>(&self, size: S) {
-    let (width, height) = size.into().to_logical::<i32>(self.scale_factor()).into();
+    let (width, height) = scale::size_to_gdk::<i32>(
+      size.into(),
+      self.scale_factor(),
+      self.scale_factor.load(Ordering::Acquire),
+    )
+    .into();
 
     if let Err(e) = self
       .window_requests_tx
@@ -652,6 +660,14 @@ impl Window {
     self.minimized.load(Ordering::Acquire)
   }
 
+  pub fn tiled_edges(&self) -> TiledEdges {
+    self
+      .window
+      .window()
+      .map(|window| util::tiled_edges(window.state()))
+      .unwrap_or_default()
+  }
+
   pub fn is_resizable(&self) -> bool {
     self.window.is_resizable()
   }
@@ -778,10 +794,12 @@ impl Window {
 
   pub fn set_cursor_position<P: Into<Position>>(&self, position: P) -> Result<(), ExternalError> {
     let inner_pos = self.inner_position().unwrap_or_default();
-    let (x, y): (i32, i32) = position
-      .into()
-      .to_logical::<i32>(self.scale_factor())
-      .into();
+    let (x, y): (i32, i32) = scale::position_to_gdk::<i32>(
+      position.into(),
+      self.scale_factor(),
+      self.scale_factor.load(Ordering::Acquire),
+    )
+    .into();
 
     if let Err(e) = self.window_requests_tx.send((
       self.window_id,
@@ -1074,12 +1092,15 @@ pub enum WindowRequest {
     transparent: bool,
     fullscreen: bool,
     cursor_moved: bool,
+    motion_compression: bool,
+    motion_history: bool,
   },
   SetVisibleOnAllWorkspaces(bool),
   ProgressBarState(ProgressBarState),
   BadgeCount(Option<i64>, Option<String>),
   SetTheme(Option<Theme>),
   BackgroundColor(CssProvider, Option<RGBA>),
+  Redraw,
 }
 
 impl Drop for Window {
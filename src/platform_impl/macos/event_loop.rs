@@ -21,7 +21,7 @@ use objc2_foundation::{MainThreadMarker, NSAutoreleasePool, NSInteger, NSPoint,
 
 use crate::{
   dpi::PhysicalPosition,
-  error::ExternalError,
+  error::{EventLoopError, ExternalError},
   event::Event,
   event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
   monitor::MonitorHandle as RootMonitorHandle,
@@ -158,6 +158,12 @@ pub struct EventLoop<T: 'static> {
 pub(crate) struct PlatformSpecificEventLoopAttributes {}
 
 impl<T> EventLoop<T> {
+  pub(crate) fn try_new(
+    attributes: &PlatformSpecificEventLoopAttributes,
+  ) -> Result<Self, EventLoopError> {
+    Ok(Self::new(attributes))
+  }
+
   pub(crate) fn new(_: &PlatformSpecificEventLoopAttributes) -> Self {
     let panic_info: Rc<PanicInfo> = Default::default();
     setup_control_flow_observers(Rc::downgrade(&panic_info));
@@ -7,12 +7,13 @@ use std::{collections::VecDeque, fmt};
 use super::ffi::{self, id, nil, CGRectContainsPoint};
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  monitor::{MonitorHandle as RootMonitorHandle, Rect, VideoMode as RootVideoMode},
 };
 use core_foundation::{
   array::{CFArrayGetCount, CFArrayGetValueAtIndex},
   base::{CFRelease, TCFType},
   string::CFString,
+  uuid::CFUUIDGetUUIDBytes,
 };
 use core_graphics::{
   display::{CGDirectDisplayID, CGDisplay, CGDisplayBounds},
@@ -213,6 +214,38 @@ impl MonitorHandle {
     self.0
   }
 
+  pub fn id(&self) -> String {
+    unsafe {
+      let uuid = ffi::CGDisplayCreateUUIDFromDisplayID(self.0);
+      if uuid.is_null() {
+        return self.0.to_string();
+      }
+      let bytes = CFUUIDGetUUIDBytes(uuid);
+      CFRelease(uuid as *const _);
+      [
+        bytes.byte0,
+        bytes.byte1,
+        bytes.byte2,
+        bytes.byte3,
+        bytes.byte4,
+        bytes.byte5,
+        bytes.byte6,
+        bytes.byte7,
+        bytes.byte8,
+        bytes.byte9,
+        bytes.byte10,
+        bytes.byte11,
+        bytes.byte12,
+        bytes.byte13,
+        bytes.byte14,
+        bytes.byte15,
+      ]
+      .iter()
+      .map(|byte| format!("{byte:02X}"))
+      .collect()
+    }
+  }
+
   pub fn size(&self) -> PhysicalSize<u32> {
     let MonitorHandle(display_id) = *self;
     let display = CGDisplay::new(display_id);
@@ -230,6 +263,30 @@ impl MonitorHandle {
     )
   }
 
+  pub fn work_area(&self) -> Rect {
+    let screen = match self.ns_screen() {
+      Some(screen) => screen,
+      None => {
+        return Rect {
+          position: self.position(),
+          size: self.size(),
+        }
+      }
+    };
+    let frame = screen.visibleFrame();
+    let scale_factor = self.scale_factor();
+    Rect {
+      position: PhysicalPosition::from_logical::<_, f64>(
+        (frame.origin.x, super::util::bottom_left_to_top_left(frame)),
+        scale_factor,
+      ),
+      size: PhysicalSize::from_logical::<_, f64>(
+        (frame.size.width, frame.size.height),
+        scale_factor,
+      ),
+    }
+  }
+
   pub fn scale_factor(&self) -> f64 {
     let screen = match self.ns_screen() {
       Some(screen) => screen,
@@ -238,6 +295,22 @@ impl MonitorHandle {
     NSScreen::backingScaleFactor(&screen) as f64
   }
 
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    let refresh_rate = CGDisplay::new(self.0).display_mode()?.refresh_rate();
+    // CGDisplayModeGetRefreshRate returns 0.0 for any display that isn't a CRT
+    (refresh_rate > 0.0).then(|| (refresh_rate * 1000.0).round() as u32)
+  }
+
+  pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+    let size = CGDisplay::new(self.0).screen_size();
+    (size.width > 0.0 && size.height > 0.0)
+      .then(|| (size.width.round() as u32, size.height.round() as u32))
+  }
+
+  pub fn icc_profile(&self) -> Option<Vec<u8>> {
+    None
+  }
+
   pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
     let cv_refresh_rate = unsafe {
       let mut display_link = std::ptr::null_mut();
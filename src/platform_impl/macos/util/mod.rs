@@ -7,14 +7,17 @@ mod cursor;
 
 pub use self::{cursor::*, r#async::*};
 
-use std::ops::{BitAnd, Deref};
+use std::{
+  ops::{BitAnd, Deref},
+  time::{Duration, Instant},
+};
 
 use core_graphics::display::CGDisplay;
 use objc2::{
   class,
   runtime::{AnyClass as Class, AnyObject as Object, Sel},
 };
-use objc2_app_kit::{NSApp, NSView, NSWindow, NSWindowStyleMask};
+use objc2_app_kit::{NSApp, NSEvent, NSView, NSWindow, NSWindowStyleMask};
 use objc2_foundation::{MainThreadMarker, NSAutoreleasePool, NSPoint, NSRange, NSRect, NSUInteger};
 
 use crate::{
@@ -23,6 +26,31 @@ use crate::{
   platform_impl::platform::ffi::{self, id, nil, BOOL, YES},
 };
 
+/// Converts the timestamp of `event` to an `Instant`.
+///
+/// `NSEvent` timestamps are seconds since boot, not counting sleep, the same clock as
+/// `CLOCK_UPTIME_RAW` behind `Instant`. Timestamps that don't look like they come from that clock
+/// fall back to the current time.
+pub fn event_time(event: &NSEvent) -> Instant {
+  // Older events than this are assumed to use another clock.
+  const MAX_AGE: Duration = Duration::from_secs(10);
+
+  let now = Instant::now();
+  let mut ts = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  if unsafe { libc::clock_gettime(libc::CLOCK_UPTIME_RAW, &mut ts) } != 0 {
+    return now;
+  }
+
+  let uptime = ts.tv_sec as f64 + ts.tv_nsec as f64 / 1e9;
+  match Duration::try_from_secs_f64(uptime - event.timestamp()) {
+    Ok(age) if age <= MAX_AGE => now.checked_sub(age).unwrap_or(now),
+    _ => now,
+  }
+}
+
 // Replace with `!` once stable
 #[derive(Debug)]
 pub enum Never {}
@@ -721,6 +721,7 @@ extern "C" fn key_down(this: &mut Object, _sel: Sel, event: &NSEvent) {
         device_id: DEVICE_ID,
         event: key_event,
         is_synthetic: false,
+        timestamp: util::event_time(event),
       },
     };
     AppState::queue_event(EventWrapper::StaticEvent(window_event));
@@ -744,6 +745,7 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: &NSEvent) {
         device_id: DEVICE_ID,
         event: create_key_event(event, false, false, false, None),
         is_synthetic: false,
+        timestamp: util::event_time(event),
       },
     };
     AppState::queue_event(EventWrapper::StaticEvent(window_event));
@@ -804,6 +806,7 @@ extern "C" fn flags_changed(this: &Object, _sel: Sel, ns_event: &NSEvent) {
               device_id: DEVICE_ID,
               event,
               is_synthetic: false,
+              timestamp: util::event_time(ns_event),
             });
             if is_pressed {
               state.phys_modifiers.insert($target_key);
@@ -913,6 +916,7 @@ extern "C" fn cancel_operation(this: &Object, _sel: Sel, _sender: id) {
         device_id: DEVICE_ID,
         event: create_key_event(&event, true, false, false, Some(key)),
         is_synthetic: false,
+        timestamp: util::event_time(&event),
       },
     };
     AppState::queue_event(EventWrapper::StaticEvent(window_event));
@@ -934,6 +938,8 @@ fn mouse_click(this: &Object, event: &NSEvent, button: MouseButton, button_state
         state: button_state,
         button,
         modifiers: event_mods(event),
+        click_count: event.clickCount().max(1) as u32,
+        timestamp: util::event_time(event),
       },
     };
 
@@ -963,12 +969,32 @@ extern "C" fn right_mouse_up(this: &NSView, _sel: Sel, event: &NSEvent) {
 
 extern "C" fn other_mouse_down(this: &NSView, _sel: Sel, event: &NSEvent) {
   mouse_motion(this, event);
-  mouse_click(this, event, MouseButton::Middle, ElementState::Pressed);
+  mouse_click(
+    this,
+    event,
+    other_mouse_button(event),
+    ElementState::Pressed,
+  );
 }
 
 extern "C" fn other_mouse_up(this: &NSView, _sel: Sel, event: &NSEvent) {
   mouse_motion(this, event);
-  mouse_click(this, event, MouseButton::Middle, ElementState::Released);
+  mouse_click(
+    this,
+    event,
+    other_mouse_button(event),
+    ElementState::Released,
+  );
+}
+
+fn other_mouse_button(event: &NSEvent) -> MouseButton {
+  let button_number: NSInteger = unsafe { msg_send![event, buttonNumber] };
+  match button_number {
+    3 => MouseButton::Back,
+    4 => MouseButton::Forward,
+    n if n > 4 => MouseButton::Other(n as u16),
+    _ => MouseButton::Middle,
+  }
 }
 
 fn mouse_motion(this: &NSView, event: &NSEvent) {
@@ -1004,6 +1030,7 @@ fn mouse_motion(this: &NSView, event: &NSEvent) {
         device_id: DEVICE_ID,
         position: logical_position.to_physical(state.get_scale_factor()),
         modifiers: event_mods(event),
+        timestamp: util::event_time(event),
       },
     };
 
@@ -1105,6 +1132,7 @@ extern "C" fn scroll_wheel(this: &NSView, _sel: Sel, event: &NSEvent) {
         delta,
         phase,
         modifiers: event_mods(event),
+        timestamp: util::event_time(event),
       },
     };
 
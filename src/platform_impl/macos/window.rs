@@ -36,8 +36,8 @@ use crate::{
     set_badge_label, set_progress_indicator,
   },
   window::{
-    CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, UserAttentionType,
-    WindowAttributes, WindowId as RootWindowId, WindowSizeConstraints,
+    CursorIcon, Fullscreen, ProgressBarState, ResizeDirection, Theme, TiledEdges,
+    UserAttentionType, WindowAttributes, WindowId as RootWindowId, WindowSizeConstraints,
   },
 };
 use core_graphics::{
@@ -1030,6 +1030,11 @@ impl UnownedWindow {
     unsafe { self.ns_window.isMiniaturized() }
   }
 
+  #[inline]
+  pub fn tiled_edges(&self) -> TiledEdges {
+    TiledEdges::empty()
+  }
+
   #[inline]
   pub fn is_resizable(&self) -> bool {
     unsafe { msg_send![&self.ns_window, isResizable] }
@@ -43,7 +43,7 @@ use windows::{
 
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize, PixelUnit},
-  error::ExternalError,
+  error::{EventLoopError, ExternalError},
   event::{DeviceEvent, Event, Force, RawKeyEvent, Touch, TouchPhase, WindowEvent},
   event_loop::{ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW},
   keyboard::{KeyCode, ModifiersState},
@@ -166,6 +166,12 @@ pub struct EventLoopWindowTarget<T: 'static> {
 }
 
 impl<T: 'static> EventLoop<T> {
+  pub(crate) fn try_new(
+    attributes: &mut PlatformSpecificEventLoopAttributes,
+  ) -> Result<EventLoop<T>, EventLoopError> {
+    Ok(Self::new(attributes))
+  }
+
   pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
     let thread_id = unsafe { GetCurrentThreadId() };
 
@@ -256,7 +262,7 @@ impl<T: 'static> EventLoop<T> {
         };
         if !handled {
           let _ = TranslateMessage(&msg);
-          DispatchMessageW(&msg);
+          util::dispatch_message(&msg);
         }
 
         if let Err(payload) = runner.take_panic_error() {
@@ -395,13 +401,13 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
       if wait_until_opt.is_some() {
         if PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
           let _ = TranslateMessage(&msg);
-          DispatchMessageW(&msg);
+          util::dispatch_message(&msg);
         }
       } else if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
         break 'main;
       } else {
         let _ = TranslateMessage(&msg);
-        DispatchMessageW(&msg);
+        util::dispatch_message(&msg);
       }
 
       if msg.message == *WAIT_UNTIL_MSG_ID {
@@ -832,7 +838,7 @@ unsafe fn flush_paint_messages<T: 'static>(
       }
 
       let _ = TranslateMessage(&msg);
-      DispatchMessageW(&msg);
+      util::dispatch_message(&msg);
     });
     true
   } else {
@@ -1007,6 +1013,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           event: event.event,
           is_synthetic: event.is_synthetic,
+          timestamp: util::message_time(window, msg),
         },
       });
     }
@@ -1342,6 +1349,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
             device_id: DEVICE_ID,
             position,
             modifiers,
+            timestamp: util::message_time(window, msg),
           },
         });
       }
@@ -1383,6 +1391,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           delta: LineDelta(0.0, value),
           phase: TouchPhase::Moved,
           modifiers,
+          timestamp: util::message_time(window, msg),
         },
       });
 
@@ -1404,6 +1413,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           delta: LineDelta(value, 0.0),
           phase: TouchPhase::Moved,
           modifiers,
+          timestamp: util::message_time(window, msg),
         },
       });
 
@@ -1422,6 +1432,12 @@ unsafe fn public_window_callback_inner<T: 'static>(
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.register_press(
+        Left,
+        GetMessageTime() as u32,
+        util::GET_X_LPARAM(lparam) as i32,
+        util::GET_Y_LPARAM(lparam) as i32,
+      );
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
@@ -1430,6 +1446,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           state: Pressed,
           button: Left,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
@@ -1441,6 +1459,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
       release_mouse(subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.click_count(Left);
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
@@ -1449,6 +1468,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           state: Released,
           button: Left,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
@@ -1460,6 +1481,12 @@ unsafe fn public_window_callback_inner<T: 'static>(
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.register_press(
+        Right,
+        GetMessageTime() as u32,
+        util::GET_X_LPARAM(lparam) as i32,
+        util::GET_Y_LPARAM(lparam) as i32,
+      );
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
@@ -1468,6 +1495,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           state: Pressed,
           button: Right,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
@@ -1479,6 +1508,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
       release_mouse(subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.click_count(Right);
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
@@ -1487,6 +1517,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           state: Released,
           button: Right,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
@@ -1498,6 +1530,12 @@ unsafe fn public_window_callback_inner<T: 'static>(
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.register_press(
+        Middle,
+        GetMessageTime() as u32,
+        util::GET_X_LPARAM(lparam) as i32,
+        util::GET_Y_LPARAM(lparam) as i32,
+      );
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
@@ -1506,6 +1544,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           state: Pressed,
           button: Middle,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
@@ -1517,6 +1557,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
       release_mouse(subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.click_count(Middle);
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
@@ -1525,46 +1566,59 @@ unsafe fn public_window_callback_inner<T: 'static>(
           state: Released,
           button: Middle,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
     }
 
     win32wm::WM_XBUTTONDOWN => {
-      use crate::event::{ElementState::Pressed, MouseButton::Other, WindowEvent::MouseInput};
-      let xbutton = util::GET_XBUTTON_WPARAM(wparam);
+      use crate::event::{ElementState::Pressed, WindowEvent::MouseInput};
+      let button = util::xbutton_to_mouse_button(util::GET_XBUTTON_WPARAM(wparam));
 
       capture_mouse(window, &mut subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.register_press(
+        button,
+        GetMessageTime() as u32,
+        util::GET_X_LPARAM(lparam) as i32,
+        util::GET_Y_LPARAM(lparam) as i32,
+      );
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
         event: MouseInput {
           device_id: DEVICE_ID,
           state: Pressed,
-          button: Other(xbutton),
+          button,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
     }
 
     win32wm::WM_XBUTTONUP => {
-      use crate::event::{ElementState::Released, MouseButton::Other, WindowEvent::MouseInput};
-      let xbutton = util::GET_XBUTTON_WPARAM(wparam);
+      use crate::event::{ElementState::Released, WindowEvent::MouseInput};
+      let button = util::xbutton_to_mouse_button(util::GET_XBUTTON_WPARAM(wparam));
 
       release_mouse(subclass_input.window_state.lock());
 
       let modifiers = update_modifiers(window, subclass_input);
+      let click_count = subclass_input.window_state.lock().mouse.click_count(button);
 
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0 as _)),
         event: MouseInput {
           device_id: DEVICE_ID,
           state: Released,
-          button: Other(xbutton),
+          button,
           modifiers,
+          click_count,
+          timestamp: util::message_time(window, msg),
         },
       });
       result = ProcResult::Value(LRESULT(0));
@@ -1626,6 +1680,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
               force: None, // WM_TOUCH doesn't support pressure information
               id: input.dwID as u64,
               device_id: DEVICE_ID,
+              timestamp: util::message_time(window, msg),
             }),
           });
         }
@@ -1762,6 +1817,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
               force,
               id: pointer_info.pointerId as u64,
               device_id: DEVICE_ID,
+              timestamp: util::message_time(window, msg),
             }),
           });
         }
@@ -7,7 +7,7 @@ use windows::{
   Win32::{
     Foundation::{HWND, LPARAM, POINT, RECT},
     Graphics::Gdi::*,
-    UI::WindowsAndMessaging::USER_DEFAULT_SCREEN_DPI,
+    UI::WindowsAndMessaging::{EDD_GET_DEVICE_INTERFACE_NAME, USER_DEFAULT_SCREEN_DPI},
   },
 };
 
@@ -19,7 +19,7 @@ use std::{
 use super::util;
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
-  monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+  monitor::{MonitorHandle as RootMonitorHandle, Rect, VideoMode as RootVideoMode},
   platform_impl::platform::{
     dpi::{dpi_to_scale_factor, get_monitor_dpi},
     window::Window,
@@ -190,6 +190,30 @@ impl MonitorHandle {
     self.name().unwrap()
   }
 
+  /// The device interface path of the monitor, which names the monitor model and the port it is
+  /// connected to, unlike `\\.\DISPLAYn` which is reassigned when monitors change.
+  pub fn id(&self) -> String {
+    let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
+    let mut device = DISPLAY_DEVICEW {
+      cb: mem::size_of::<DISPLAY_DEVICEW>() as u32,
+      ..Default::default()
+    };
+    // `szDevice` names the adapter output, the first device attached to it is the monitor.
+    let found = unsafe {
+      EnumDisplayDevicesW(
+        PCWSTR::from_raw(monitor_info.szDevice.as_ptr()),
+        0,
+        &mut device,
+        EDD_GET_DEVICE_INTERFACE_NAME,
+      )
+    };
+    if found.as_bool() && device.DeviceID[0] != 0 {
+      util::wchar_ptr_to_string(PCWSTR::from_raw(device.DeviceID.as_ptr()))
+    } else {
+      self.native_identifier()
+    }
+  }
+
   #[inline]
   pub fn hmonitor(&self) -> HMONITOR {
     HMONITOR(self.0 as _)
@@ -215,11 +239,60 @@ impl MonitorHandle {
     }
   }
 
+  #[inline]
+  pub fn work_area(&self) -> Rect {
+    let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
+    let rect = monitor_info.monitorInfo.rcWork;
+    Rect {
+      position: PhysicalPosition::new(rect.left, rect.top),
+      size: PhysicalSize::new(
+        (rect.right - rect.left) as u32,
+        (rect.bottom - rect.top) as u32,
+      ),
+    }
+  }
+
   #[inline]
   pub fn scale_factor(&self) -> f64 {
     dpi_to_scale_factor(get_monitor_dpi(self.hmonitor()).unwrap_or(USER_DEFAULT_SCREEN_DPI))
   }
 
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    let monitor_info = get_monitor_info(self.hmonitor()).ok()?;
+    let device_name = PCWSTR::from_raw(monitor_info.szDevice.as_ptr());
+    unsafe {
+      let mut mode: DEVMODEW = mem::zeroed();
+      mode.dmSize = mem::size_of_val(&mode) as u16;
+      if !EnumDisplaySettingsExW(
+        device_name,
+        ENUM_CURRENT_SETTINGS,
+        &mut mode,
+        ENUM_DISPLAY_SETTINGS_FLAGS(0),
+      )
+      .as_bool()
+      {
+        return None;
+      }
+      // 0 and 1 mean the hardware's default rate.
+      if mode.dmFields & DM_DISPLAYFREQUENCY == DM_DISPLAYFREQUENCY && mode.dmDisplayFrequency > 1 {
+        Some(mode.dmDisplayFrequency * 1000)
+      } else {
+        None
+      }
+    }
+  }
+
+  #[inline]
+  pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+    None
+  }
+
+  #[inline]
+  pub fn icc_profile(&self) -> Option<Vec<u8>> {
+    None
+  }
+
   #[inline]
   pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
     // EnumDisplaySettingsExW can return duplicate values (or some of the
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+  cell::Cell,
   io,
   iter::once,
   mem,
@@ -10,10 +11,12 @@ use std::{
   os::windows::prelude::OsStrExt,
   slice,
   sync::atomic::{AtomicBool, Ordering},
+  time::{Duration, Instant},
 };
 
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
+  event::MouseButton,
   window::CursorIcon,
 };
 
@@ -24,7 +27,7 @@ use windows::{
     Foundation::{COLORREF, FARPROC, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
     Globalization::lstrlenW,
     Graphics::Gdi::{ClientToScreen, InvalidateRgn, HMONITOR},
-    System::LibraryLoader::*,
+    System::{LibraryLoader::*, SystemInformation::GetTickCount},
     UI::{
       HiDpi::*,
       Input::KeyboardAndMouse::*,
@@ -33,6 +36,46 @@ use windows::{
   },
 };
 
+thread_local! {
+  /// The window and message `DispatchMessageW` is delivering, whose time `GetMessageTime` returns.
+  static DISPATCHED_MESSAGE: Cell<Option<(isize, u32)>> = const { Cell::new(None) };
+}
+
+/// Dispatches a message retrieved from the message queue, remembering it for [`message_time`].
+pub unsafe fn dispatch_message(msg: &MSG) {
+  let previous = DISPATCHED_MESSAGE
+    .with(|dispatched| dispatched.replace(Some((msg.hwnd.0 as isize, msg.message))));
+  DispatchMessageW(msg);
+  DISPATCHED_MESSAGE.with(|dispatched| dispatched.set(previous));
+}
+
+/// Converts the time of the message `msg` of `window` being processed, from `GetMessageTime`, to
+/// an `Instant`.
+///
+/// Message times are milliseconds since boot, the clock of `GetTickCount`, truncated to 32 bits.
+/// `GetMessageTime` only knows the time of messages posted to the queue, so sent messages and
+/// times that don't look like they come from that clock fall back to the current time.
+pub fn message_time(window: HWND, msg: u32) -> Instant {
+  // Older messages than this are assumed to use another clock.
+  const MAX_AGE: Duration = Duration::from_secs(10);
+
+  let now = Instant::now();
+  let posted = DISPATCHED_MESSAGE.with(|dispatched| dispatched.get())
+    == Some((window.0 as isize, msg))
+    && !unsafe { InSendMessage() }.as_bool();
+  if !posted {
+    return now;
+  }
+
+  let (time, ticks) = unsafe { (GetMessageTime() as u32, GetTickCount()) };
+  let age = Duration::from_millis(ticks.wrapping_sub(time) as u64);
+  if age > MAX_AGE {
+    now
+  } else {
+    now.checked_sub(age).unwrap_or(now)
+  }
+}
+
 pub fn has_flag<T>(bitset: T, flag: T) -> bool
 where
   T: Copy + PartialEq + BitAnd<T, Output = T>,
@@ -393,6 +436,15 @@ pub fn GET_XBUTTON_WPARAM(wparam: WPARAM) -> u16 {
   ((wparam.0 & 0xFFFF_0000) >> 16) as u16
 }
 
+/// Maps the button of a `WM_XBUTTONDOWN` or `WM_XBUTTONUP` message to a `MouseButton`.
+pub fn xbutton_to_mouse_button(xbutton: u16) -> MouseButton {
+  match xbutton {
+    1 => MouseButton::Back,
+    2 => MouseButton::Forward,
+    _ => MouseButton::Other(xbutton),
+  }
+}
+
 /// Implementation of the `PRIMARYLANGID` macro.
 #[allow(non_snake_case)]
 #[inline]
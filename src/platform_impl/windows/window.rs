@@ -51,7 +51,7 @@ use crate::{
     OsError, Parent, PlatformSpecificWindowBuilderAttributes, WindowId,
   },
   window::{
-    CursorIcon, Fullscreen, ProgressBarState, ProgressState, ResizeDirection, Theme,
+    CursorIcon, Fullscreen, ProgressBarState, ProgressState, ResizeDirection, Theme, TiledEdges,
     UserAttentionType, WindowAttributes, WindowSizeConstraints, RGBA,
   },
 };
@@ -618,6 +618,11 @@ impl Window {
     unsafe { IsIconic(self.hwnd()) }.as_bool()
   }
 
+  #[inline]
+  pub fn tiled_edges(&self) -> TiledEdges {
+    TiledEdges::empty()
+  }
+
   #[inline]
   pub fn is_resizable(&self) -> bool {
     let window_state = self.window_state.lock();
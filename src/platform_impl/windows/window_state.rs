@@ -4,6 +4,7 @@
 
 use crate::{
   dpi::PhysicalPosition,
+  event::MouseButton,
   icon::Icon,
   keyboard::ModifiersState,
   platform_impl::platform::{event_loop, minimal_ime::MinimalIme, util},
@@ -14,7 +15,7 @@ use std::io;
 use windows::Win32::{
   Foundation::{HWND, LPARAM, RECT, WPARAM},
   Graphics::Gdi::InvalidateRgn,
-  UI::WindowsAndMessaging::*,
+  UI::{Input::KeyboardAndMouse::GetDoubleClickTime, WindowsAndMessaging::*},
 };
 
 /// Contains information about states and the window that the callback is going to use.
@@ -64,6 +65,9 @@ pub struct MouseProperties {
   pub capture_count: u32,
   cursor_flags: CursorFlags,
   pub last_position: Option<PhysicalPosition<f64>>,
+  // Button, message time and client position of the last press.
+  last_press: Option<(MouseButton, u32, i32, i32)>,
+  click_count: u32,
 }
 
 bitflags! {
@@ -136,6 +140,8 @@ impl WindowState {
         capture_count: 0,
         cursor_flags: CursorFlags::empty(),
         last_position: None,
+        last_press: None,
+        click_count: 0,
       },
 
       size_constraints: attributes.inner_size_constraints,
@@ -206,6 +212,38 @@ impl WindowState {
 }
 
 impl MouseProperties {
+  /// Registers a press at the client position `(x, y)` and returns its click count, using the
+  /// system double-click time and rectangle.
+  pub fn register_press(&mut self, button: MouseButton, time: u32, x: i32, y: i32) -> u32 {
+    let (max_time, max_dx, max_dy) = unsafe {
+      (
+        GetDoubleClickTime(),
+        GetSystemMetrics(SM_CXDOUBLECLK) / 2,
+        GetSystemMetrics(SM_CYDOUBLECLK) / 2,
+      )
+    };
+
+    let in_row = self
+      .last_press
+      .is_some_and(|(last_button, last_time, last_x, last_y)| {
+        last_button == button
+          && time.wrapping_sub(last_time) <= max_time
+          && (x - last_x).abs() <= max_dx
+          && (y - last_y).abs() <= max_dy
+      });
+    self.click_count = if in_row { self.click_count + 1 } else { 1 };
+    self.last_press = Some((button, time, x, y));
+    self.click_count
+  }
+
+  /// Returns the click count of the last press of `button`.
+  pub fn click_count(&self, button: MouseButton) -> u32 {
+    match self.last_press {
+      Some((last_button, ..)) if last_button == button => self.click_count,
+      _ => 1,
+    }
+  }
+
   pub fn cursor_flags(&self) -> CursorFlags {
     self.cursor_flags
   }
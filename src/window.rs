@@ -621,6 +621,8 @@ impl Window {
   ///
   /// ## Platform-specific
   ///
+  /// - **Linux:** On X11, derived from `Xft.dpi` or the monitor's physical size and can be
+  ///   fractional. Can be overridden with `EventLoopBuilderExtUnix::with_scale_factor_override`.
   /// - **Android:** Always returns 1.0.
   /// - **iOS:** Can only be called on the main thread. Returns the underlying `UIView`'s
   ///   [`contentScaleFactor`].
@@ -943,6 +945,20 @@ impl Window {
     self.window.is_minimized()
   }
 
+  /// Gets the edges of the window that are tiled, e.g. after snapping it to half of the screen.
+  ///
+  /// Client-side decorations usually drop their shadow and rounded corners on tiled edges. Changes
+  /// are reported in [`WindowEvent::StateChanged`](crate::event::WindowEvent::StateChanged).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Window managers that don't report tiling per edge report every edge as tiled.
+  /// - **macOS / Windows / iOS / Android:** Unsupported, returns no edges.
+  #[inline]
+  pub fn tiled_edges(&self) -> TiledEdges {
+    self.window.tiled_edges()
+  }
+
   /// Gets the window's current visibility state.
   ///
   /// ## Platform-specific
@@ -1463,6 +1479,43 @@ pub enum Fullscreen {
   Borderless(Option<MonitorHandle>),
 }
 
+/// A snapshot of the state of a window, see [`WindowEvent::StateChanged`](crate::event::WindowEvent::StateChanged).
+#[non_exhaustive]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowState {
+  /// The window is maximized.
+  pub maximized: bool,
+  /// The window is minimized.
+  pub minimized: bool,
+  /// The window is fullscreen.
+  pub fullscreen: bool,
+  /// The window has the keyboard focus.
+  pub focused: bool,
+  /// The edges of the window constrained by a tiling layout or by monitor edges.
+  pub tiled_edges: TiledEdges,
+  /// The edges the window can be resized from while it is tiled. Window managers that don't
+  /// report edge constraints leave this empty.
+  pub resizable_edges: TiledEdges,
+  /// The window is shown on all workspaces.
+  pub sticky: bool,
+  /// The window is kept above other windows.
+  pub above: bool,
+  /// The window is kept below other windows.
+  pub below: bool,
+}
+
+bitflags! {
+  /// A set of edges of a window, e.g. the ones that are tiled, i.e. constrained by a neighbouring
+  /// window or a monitor edge.
+  #[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+  pub struct TiledEdges: u8 {
+    const TOP = 1 << 0;
+    const RIGHT = 1 << 1;
+    const BOTTOM = 1 << 2;
+    const LEFT = 1 << 3;
+  }
+}
+
 #[non_exhaustive]
 #[derive(Default, Clone, Copy, Debug, PartialEq)]
 pub enum Theme {
@@ -9,6 +9,7 @@ use tao::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
   event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase},
   keyboard::{Key, KeyCode, KeyLocation, ModifiersState},
+  monitor::MonitorId,
   window::CursorIcon,
 };
 
@@ -20,6 +21,11 @@ fn window_serde() {
   needs_serde::<CursorIcon>();
 }
 
+#[test]
+fn monitor_serde() {
+  needs_serde::<MonitorId>();
+}
+
 #[test]
 fn events_serde() {
   needs_serde::<TouchPhase>();